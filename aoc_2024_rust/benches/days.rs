@@ -0,0 +1,141 @@
+//! One benchmark group per day, timing `parse_input`, `solve_part1` and
+//! `solve_part2` on the real input in `AOC_INPUT_DIR` when there is one, and
+//! on a generated input (see `gen::generate`) otherwise.
+//!
+//! Save a baseline with `cargo bench -- --save-baseline main`, then compare
+//! a change against it with `cargo bench -- --baseline main`.
+
+use aoc_2024_rust::aoc::options::Options;
+use aoc_2024_rust::aoc::{day1, day10, day11, day14, day15, day2, day3, day4, day5, day6};
+use aoc_2024_rust::aoc::{day7, day8, gen, input};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::time::Duration;
+
+fn input_for_day(day: u8) -> (&'static str, String) {
+    match input::try_read_input_for_day(day) {
+        Some(input) => ("real", input),
+        None => (
+            "generated",
+            gen::generate(day as usize, &Options::default()),
+        ),
+    }
+}
+
+/// Benches `parse` and both parts of `day` as the `day<N>` group
+fn bench_day<P, S1, S2, R1, R2, R3>(
+    c: &mut Criterion,
+    day: u8,
+    parse: P,
+    solve_part1: S1,
+    solve_part2: S2,
+) where
+    P: Fn(&String) -> R1,
+    S1: Fn(&String) -> R2,
+    S2: Fn(&String) -> R3,
+{
+    let (kind, input) = input_for_day(day);
+    let mut group = c.benchmark_group(format!("day{day}"));
+    // The slowest parts take seconds per run
+    group.sample_size(10);
+    group.warm_up_time(Duration::from_millis(500));
+    group.bench_with_input(BenchmarkId::new("parse_input", kind), &input, |b, input| {
+        b.iter(|| parse(black_box(input)))
+    });
+    group.bench_with_input(BenchmarkId::new("solve_part1", kind), &input, |b, input| {
+        b.iter(|| solve_part1(black_box(input)))
+    });
+    group.bench_with_input(BenchmarkId::new("solve_part2", kind), &input, |b, input| {
+        b.iter(|| solve_part2(black_box(input)))
+    });
+    group.finish();
+}
+
+fn bench_days(c: &mut Criterion) {
+    bench_day(
+        c,
+        1,
+        day1::parse_input,
+        day1::solve_part1,
+        day1::solve_part2,
+    );
+    bench_day(
+        c,
+        2,
+        day2::parse_input,
+        day2::solve_part1,
+        day2::solve_part2,
+    );
+    bench_day(
+        c,
+        3,
+        day3::parse_input,
+        day3::solve_part1,
+        day3::solve_part2,
+    );
+    bench_day(
+        c,
+        4,
+        day4::parse_input,
+        day4::solve_part1,
+        day4::solve_part2,
+    );
+    bench_day(
+        c,
+        5,
+        day5::parse_input,
+        day5::solve_part1,
+        day5::solve_part2,
+    );
+    bench_day(
+        c,
+        6,
+        day6::parse_input,
+        day6::solve_part1,
+        day6::solve_part2,
+    );
+    bench_day(
+        c,
+        7,
+        day7::parse_input,
+        day7::solve_part1,
+        day7::solve_part2,
+    );
+    bench_day(
+        c,
+        8,
+        day8::parse_input,
+        day8::solve_part1,
+        day8::solve_part2,
+    );
+    bench_day(
+        c,
+        10,
+        day10::parse_input,
+        day10::solve_part1,
+        day10::solve_part2,
+    );
+    bench_day(
+        c,
+        11,
+        day11::parse_input,
+        day11::solve_part1,
+        day11::solve_part2,
+    );
+    bench_day(
+        c,
+        14,
+        |input: &String| day14::parse_input(input, &day14::XY::new(101, 103)),
+        day14::solve_part1,
+        day14::solve_part2,
+    );
+    bench_day(
+        c,
+        15,
+        day15::parse_input,
+        day15::solve_part1,
+        day15::solve_part2,
+    );
+}
+
+criterion_group!(benches, bench_days);
+criterion_main!(benches);
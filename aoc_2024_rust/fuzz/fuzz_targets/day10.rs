@@ -0,0 +1,10 @@
+#![no_main]
+
+use aoc_2024_rust::aoc;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        let _ = aoc::try_solve(10, input);
+    }
+});
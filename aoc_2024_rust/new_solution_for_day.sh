@@ -28,11 +28,11 @@ pub fn run() {
     println!("\tPart2: {part2}");
 }
 
-fn solve_part1(_input: &String) -> String {
+pub fn solve_part1(_input: &String) -> String {
     String::new()
 }
 
-fn solve_part2(_input: &String) -> String {
+pub fn solve_part2(_input: &String) -> String {
     String::new()
 }
 
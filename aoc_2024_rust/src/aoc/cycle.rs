@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A cycle in the sequence `initial, step(initial), step(step(initial)), ...`
+///
+/// The sequence enters the cycle after `start` steps, at `state`, and gets back
+/// to `state` every `length` steps after that.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Cycle<S> {
+    pub start: usize,
+    pub length: usize,
+    pub state: S,
+}
+
+/// Floyd's tortoise and hare. Only keeps two states around, but needs `step`
+/// to be called about three times per state before the cycle.
+pub fn floyd<S: Clone + PartialEq>(initial: S, step: impl Fn(&S) -> S) -> Cycle<S> {
+    let mut tortoise = step(&initial);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&step(&hare));
+    }
+
+    let mut start = 0;
+    tortoise = initial;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    let mut length = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        length += 1;
+    }
+
+    Cycle {
+        start,
+        length,
+        state: tortoise,
+    }
+}
+
+/// Brent's algorithm. Like Floyd's it keeps two states around, but it calls
+/// `step` fewer times.
+pub fn brent<S: Clone + PartialEq>(initial: S, step: impl Fn(&S) -> S) -> Cycle<S> {
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+
+    let mut tortoise = initial.clone();
+    let mut hare = initial;
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    Cycle {
+        start,
+        length,
+        state: tortoise,
+    }
+}
+
+/// Remembers every state it has seen, so it calls `step` once per state.
+pub fn find_cycle<S: Clone + Eq + Hash>(initial: S, step: impl Fn(&S) -> S) -> Cycle<S> {
+    let mut seen = HashMap::<S, usize>::new();
+    let mut state = initial;
+    let mut ix = 0;
+    loop {
+        if let Some(start) = seen.get(&state) {
+            return Cycle {
+                start: *start,
+                length: ix - start,
+                state,
+            };
+        }
+        let next = step(&state);
+        seen.insert(state, ix);
+        state = next;
+        ix += 1;
+    }
+}
+
+/// The state after `n` steps. Once a state repeats, the rest of the way is
+/// skipped using the cycle.
+pub fn state_at<S: Clone + Eq + Hash>(initial: S, step: impl Fn(&S) -> S, n: usize) -> S {
+    let mut seen = HashMap::<S, usize>::new();
+    let mut history = Vec::<S>::new();
+    let mut state = initial;
+    for ix in 0..n {
+        if let Some(start) = seen.get(&state) {
+            let length = ix - start;
+            return history[start + (n - start) % length].clone();
+        }
+        seen.insert(state.clone(), ix);
+        let next = step(&state);
+        history.push(state);
+        state = next;
+    }
+
+    state
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 0 -> 1 -> 2 -> 3 -> 4 -> 5 -> 6 -> 3 -> ...
+    fn step(n: &u32) -> u32 {
+        if *n == 6 {
+            3
+        } else {
+            n + 1
+        }
+    }
+
+    #[test]
+    fn test_floyd() {
+        let cycle = floyd(0, step);
+        assert_eq!(
+            cycle,
+            Cycle {
+                start: 3,
+                length: 4,
+                state: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_brent() {
+        let cycle = brent(0, step);
+        assert_eq!(
+            cycle,
+            Cycle {
+                start: 3,
+                length: 4,
+                state: 3
+            }
+        );
+        assert_eq!(brent(4, step), floyd(4, step));
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        assert_eq!(find_cycle(0, step), brent(0, step));
+        assert_eq!(find_cycle(5, step), brent(5, step));
+
+        // Fixed point
+        let cycle = find_cycle(7, |n| n / 2);
+        assert_eq!(
+            cycle,
+            Cycle {
+                start: 3,
+                length: 1,
+                state: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_state_at() {
+        assert_eq!(state_at(0, step, 0), 0);
+        assert_eq!(state_at(0, step, 2), 2);
+        assert_eq!(state_at(0, step, 7), 3);
+        assert_eq!(state_at(0, step, 1_000_000_000), 4);
+
+        let mut state = 0;
+        for n in 0..50 {
+            assert_eq!(state_at(0, step, n), state, "failed at step {n}");
+            state = step(&state);
+        }
+    }
+}
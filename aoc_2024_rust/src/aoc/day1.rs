@@ -1,7 +1,18 @@
-use crate::aoc::input::read_input_for_day;
+use crate::aoc::input::{read_input_for_day, ParseError};
+use crate::aoc::options::Options;
 use std::collections::HashMap;
+use std::fmt;
+use std::io::BufRead;
+use std::str::FromStr;
 
 pub fn run() {
+    run_with(&Options::default());
+}
+
+/// `--metric=<metrics>` also compares the lists with each of the comma
+/// separated metrics: `l1`, `l2`, `max` or `similarity`, see `Metric`.
+/// `--explain` prints how the answers add up, `--explain=csv` as CSV.
+pub fn run_with(options: &Options) {
     println!("Day 1 Solutions");
     println!("---------------");
     let input = read_input_for_day(1);
@@ -9,59 +20,305 @@ pub fn run() {
     let part2 = solve_part2(&input);
     println!("\tPart1: {part1}");
     println!("\tPart2: {part2}");
-}
 
-fn clean_input(input: &str) -> String {
-    String::from(input)
-        .split_whitespace()
-        .collect::<Vec<&str>>()
-        .join(" ")
+    let metrics = options.list::<Metric>("metric");
+    if !metrics.is_empty() {
+        let columns = parse_input(&input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
+        for metric in metrics {
+            println!("\t{metric}: {}", compare(&columns, metric));
+        }
+    }
+    if let Some(format) = options.get("explain") {
+        let columns = parse_input(&input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
+        match format {
+            "" | "text" => println!("{}", explain_text(&columns)),
+            "csv" => println!("{}", explain_csv(&columns)),
+            _ => panic!("Unknown explain format {format:?}, expected text or csv"),
+        }
+    }
 }
 
 type Num = i32;
+/// One list of location ids per column of the input
+pub type Columns = Vec<Vec<Num>>;
+
+/// Reads the lists a line at a time without copying the input, so very long
+/// lists can come straight from a file. The first line sets the number of
+/// lists, at least 2. Blank lines are skipped.
+pub fn parse_lines(mut reader: impl BufRead) -> Result<Columns, ParseError> {
+    let mut columns = Columns::new();
+    let mut line = String::new();
+    let mut line_num = 0;
+    loop {
+        line.clear();
+        let n_read = reader
+            .read_line(&mut line)
+            .map_err(|err| ParseError::at_line(line_num + 1, err.to_string()))?;
+        if n_read == 0 {
+            break;
+        }
+        line_num += 1;
+        if line.trim().is_empty() {
+            continue;
+        }
 
-fn parse_input(input: &String) -> (Vec<Num>, Vec<Num>) {
-    let nums = clean_input(input)
-        .split(" ")
-        .map(|s| String::from(s).parse::<Num>().unwrap())
-        .collect::<Vec<Num>>();
-
-    let mut a = Vec::new();
-    let mut b = Vec::new();
-    for i in 0..nums.len() {
-        if i % 2 == 0 {
-            a.push(nums[i]);
-        } else {
-            b.push(nums[i]);
+        let n_ids = line.split_whitespace().count();
+        if columns.is_empty() {
+            if n_ids < 2 {
+                return Err(ParseError::at_line(
+                    line_num,
+                    format!("expected at least 2 location ids, found {n_ids}"),
+                ));
+            }
+            columns.resize(n_ids, Vec::new());
+        } else if n_ids != columns.len() {
+            return Err(ParseError::at_line(
+                line_num,
+                format!("expected {} location ids, found {n_ids}", columns.len()),
+            ));
+        }
+        for (id, column) in line.split_whitespace().zip(columns.iter_mut()) {
+            let id = id.parse::<Num>().map_err(|_| {
+                ParseError::at_line(line_num, format!("invalid location id {id:?}"))
+            })?;
+            column.push(id);
         }
     }
 
-    (a, b)
+    Ok(columns)
+}
+
+pub fn parse_input(input: &String) -> Result<Columns, ParseError> {
+    parse_lines(input.as_bytes())
 }
 
-fn solve_part1(input: &String) -> Num {
-    let (mut a, mut b) = parse_input(&input);
+/// 128 bit, so no sum of `Num`s or of their squares can overflow
+pub type Total = i128;
 
-    // Find sum of "distances" between sorted list elements
-    a.sort();
-    b.sort();
-    a.into_iter()
-        .zip(b.into_iter())
-        .fold(0, |acc, (ai, bi)| acc + (ai - bi).abs())
+/// How to compare two lists of location ids
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Metric {
+    /// Sum of the distances between the sorted lists
+    L1,
+    /// Sum of the squared distances between the sorted lists, squared so it stays exact
+    L2,
+    /// Largest distance between the sorted lists
+    Max,
+    /// Each id of the first list times how often it's in the second one
+    Similarity,
 }
 
-fn solve_part2(input: &String) -> Num {
-    let (a, b) = parse_input(&input);
+impl FromStr for Metric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "l1" => Ok(Metric::L1),
+            "l2" => Ok(Metric::L2),
+            "max" => Ok(Metric::Max),
+            "similarity" => Ok(Metric::Similarity),
+            _ => Err(format!("Unknown metric {s:?}")),
+        }
+    }
+}
 
-    let mut bmap = HashMap::<Num, Num>::new();
-    for bi in b.into_iter() {
-        bmap.entry(bi).and_modify(|x| *x += 1).or_insert(1);
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Metric::L1 => "l1",
+            Metric::L2 => "l2",
+            Metric::Max => "max",
+            Metric::Similarity => "similarity",
+        };
+        write!(f, "{name}")
     }
+}
+
+fn sorted(list: &[Num]) -> Vec<Num> {
+    let mut list = list.to_vec();
+    list.sort();
+    list
+}
 
-    // Find the similarity score between lists
-    a.into_iter().fold(0, |similarity, ai| {
-        similarity + ai * bmap.get(&ai).unwrap_or(&0)
-    })
+/// A row of the two lists paired up after sorting them
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Pairing {
+    pub left: Num,
+    pub right: Num,
+    pub distance: Total,
+}
+
+pub fn pairings(a: &[Num], b: &[Num]) -> Vec<Pairing> {
+    sorted(a)
+        .into_iter()
+        .zip(sorted(b))
+        .map(|(ai, bi)| Pairing {
+            left: ai,
+            right: bi,
+            distance: (Total::from(ai) - Total::from(bi)).abs(),
+        })
+        .collect()
+}
+
+fn counts_of(list: &[Num]) -> HashMap<Num, Total> {
+    let mut counts = HashMap::<Num, Total>::new();
+    for id in list {
+        counts.entry(*id).and_modify(|x| *x += 1).or_insert(1);
+    }
+    counts
+}
+
+/// What an id of the first list adds to the similarity, for all of its
+/// `left_count` appearances together
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Contribution {
+    pub id: Num,
+    pub left_count: Total,
+    pub right_count: Total,
+    pub contribution: Total,
+}
+
+/// Contributions of the ids of `a` to its similarity to `b`, by id
+pub fn contributions(a: &[Num], b: &[Num]) -> Vec<Contribution> {
+    let (acounts, bcounts) = (counts_of(a), counts_of(b));
+    let mut contributions = acounts
+        .into_iter()
+        .map(|(id, left_count)| {
+            let right_count = *bcounts.get(&id).unwrap_or(&0);
+            Contribution {
+                id,
+                left_count,
+                right_count,
+                contribution: Total::from(id) * left_count * right_count,
+            }
+        })
+        .collect::<Vec<Contribution>>();
+    contributions.sort_by_key(|c| c.id);
+
+    contributions
+}
+
+/// Compares list `a` to list `b` with `metric`
+pub fn compare_pair(a: &[Num], b: &[Num], metric: Metric) -> Total {
+    let distances = || pairings(a, b).into_iter().map(|pairing| pairing.distance);
+    match metric {
+        Metric::L1 => distances().sum(),
+        Metric::L2 => distances().map(|d| d * d).sum(),
+        Metric::Max => distances().max().unwrap_or(0),
+        Metric::Similarity => {
+            let bmap = counts_of(b);
+            a.iter().fold(0, |similarity, ai| {
+                similarity + Total::from(*ai) * bmap.get(ai).unwrap_or(&0)
+            })
+        }
+    }
+}
+
+/// Pairs of lists to compare, by index, every list with every later one
+fn list_pairs(columns: &Columns) -> impl Iterator<Item = (usize, usize)> + '_ {
+    (0..columns.len()).flat_map(move |ix| (ix + 1..columns.len()).map(move |jx| (ix, jx)))
+}
+
+/// Compares every list to every later one with `metric`, and sums the
+/// results (or takes the largest for `Metric::Max`).
+pub fn compare(columns: &Columns, metric: Metric) -> Total {
+    let results =
+        list_pairs(columns).map(|(ix, jx)| compare_pair(&columns[ix], &columns[jx], metric));
+    match metric {
+        Metric::Max => results.max().unwrap_or(0),
+        _ => results.sum(),
+    }
+}
+
+/// The sorted pairings with their distances and the similarity contributions
+/// of every pair of lists, as aligned text.
+pub fn explain_text(columns: &Columns) -> String {
+    let mut lines = Vec::<String>::new();
+    for (ix, jx) in list_pairs(columns) {
+        let (a, b) = (&columns[ix], &columns[jx]);
+        lines.push(format!("Lists {} and {}, sorted", ix + 1, jx + 1));
+        lines.push(format!(
+            "{:>8} {:>12} {:>12} {:>12}",
+            "row", "left", "right", "distance"
+        ));
+        for (row, p) in pairings(a, b).iter().enumerate() {
+            lines.push(format!(
+                "{:>8} {:>12} {:>12} {:>12}",
+                row + 1,
+                p.left,
+                p.right,
+                p.distance
+            ));
+        }
+        lines.push(format!("distance: {}", compare_pair(a, b, Metric::L1)));
+        lines.push(String::new());
+
+        lines.push(format!("Similarity of list {} to list {}", ix + 1, jx + 1));
+        lines.push(format!(
+            "{:>12} {:>8} {:>8} {:>16}",
+            "id", "left", "right", "contribution"
+        ));
+        for c in contributions(a, b) {
+            lines.push(format!(
+                "{:>12} {:>8} {:>8} {:>16}",
+                c.id, c.left_count, c.right_count, c.contribution
+            ));
+        }
+        lines.push(format!(
+            "similarity: {}",
+            compare_pair(a, b, Metric::Similarity)
+        ));
+        lines.push(String::new());
+    }
+
+    lines.join("\n")
+}
+
+/// `explain_text` as CSV. Distance rows are `lists,distance,<row>,<left>,<right>,<distance>`
+/// and similarity rows `lists,similarity,<id>,<left count>,<right count>,<contribution>`,
+/// where `lists` is e.g. `1-2`.
+pub fn explain_csv(columns: &Columns) -> String {
+    let mut lines = vec!["lists,kind,key,left,right,value".to_string()];
+    for (ix, jx) in list_pairs(columns) {
+        let (a, b) = (&columns[ix], &columns[jx]);
+        let lists = format!("{}-{}", ix + 1, jx + 1);
+        for (row, p) in pairings(a, b).iter().enumerate() {
+            lines.push(format!(
+                "{lists},distance,{},{},{},{}",
+                row + 1,
+                p.left,
+                p.right,
+                p.distance
+            ));
+        }
+        for c in contributions(a, b) {
+            lines.push(format!(
+                "{lists},similarity,{},{},{},{}",
+                c.id, c.left_count, c.right_count, c.contribution
+            ));
+        }
+    }
+
+    lines.join("\n")
+}
+
+pub fn solve_part1(input: &String) -> Total {
+    let columns = parse_input(input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
+    compare(&columns, Metric::L1)
+}
+
+pub fn solve_part2(input: &String) -> Total {
+    let columns = parse_input(input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
+    compare(&columns, Metric::Similarity)
+}
+
+/// Both answers, or why `input` can't be solved
+pub fn try_solve(input: &String) -> Result<(String, String), ParseError> {
+    parse_input(input)?;
+    Ok((
+        solve_part1(input).to_string(),
+        solve_part2(input).to_string(),
+    ))
 }
 
 #[cfg(test)]
@@ -82,14 +339,103 @@ mod test_part_1 {
 
     #[test]
     fn test_parse_input() {
-        let parsed_input = parse_input(&get_input());
-        assert_eq!(parsed_input.0, vec![3, 4, 2, 1, 3, 3]);
+        let parsed_input = parse_input(&get_input()).unwrap();
+        assert_eq!(parsed_input[0], vec![3, 4, 2, 1, 3, 3]);
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        // Any reader works, and lines can end in \r\n
+        let reader = std::io::Cursor::new(b"3   4\r\n4   3\r\n".to_vec());
+        assert_eq!(parse_lines(reader).unwrap(), vec![vec![3, 4], vec![4, 3]]);
+        assert_eq!(parse_lines("".as_bytes()).unwrap(), Columns::new());
+        assert_eq!(
+            parse_lines("1 2 3\n4 5 6".as_bytes()).unwrap(),
+            vec![vec![1, 4], vec![2, 5], vec![3, 6]]
+        );
+        assert_eq!(
+            parse_lines("\n1".as_bytes()),
+            Err(ParseError::at_line(
+                2,
+                "expected at least 2 location ids, found 1"
+            ))
+        );
     }
 
     #[test]
     fn test_full() {
         assert_eq!(11, solve_part1(&get_input()));
     }
+
+    #[test]
+    fn test_compare() {
+        let columns = parse_input(&get_input()).unwrap();
+        // Sorted pairs are 1-3, 2-3, 3-3, 3-4, 3-5 and 4-9
+        assert_eq!(compare(&columns, Metric::L1), 11);
+        assert_eq!(compare(&columns, Metric::L2), 4 + 1 + 0 + 1 + 4 + 25);
+        assert_eq!(compare(&columns, Metric::Max), 5);
+        assert_eq!(compare(&columns, Metric::Similarity), 31);
+
+        // Every list against every later one
+        let columns = vec![vec![1, 2], vec![2, 4], vec![7, 1]];
+        assert_eq!(compare(&columns, Metric::L1), (1 + 2) + (0 + 5) + (1 + 3));
+        assert_eq!(compare(&columns, Metric::Max), 5);
+        assert_eq!(compare(&columns, Metric::Similarity), 2 + 1 + 0);
+
+        // Squares of the largest distances don't overflow
+        let columns = vec![vec![Num::MIN; 3], vec![Num::MAX; 3]];
+        assert_eq!(
+            compare(&columns, Metric::L2),
+            3 * (u32::MAX as Total).pow(2)
+        );
+    }
+
+    #[test]
+    fn test_explain() {
+        let columns = parse_input(&get_input()).unwrap();
+        let distances = pairings(&columns[0], &columns[1]);
+        assert_eq!(
+            distances[5],
+            Pairing {
+                left: 4,
+                right: 9,
+                distance: 5
+            }
+        );
+        assert_eq!(distances.iter().map(|p| p.distance).sum::<Total>(), 11);
+        let similarities = contributions(&columns[0], &columns[1]);
+        assert_eq!(
+            similarities.iter().map(|c| c.id).collect::<Vec<Num>>(),
+            vec![1, 2, 3, 4]
+        );
+        assert_eq!(
+            similarities[2],
+            Contribution {
+                id: 3,
+                left_count: 3,
+                right_count: 3,
+                contribution: 27
+            }
+        );
+
+        let text = explain_text(&columns);
+        assert!(text.contains("distance: 11\n"));
+        assert!(text.contains("similarity: 31\n"));
+        let csv = explain_csv(&columns);
+        let lines = csv.lines().collect::<Vec<&str>>();
+        assert_eq!(lines[0], "lists,kind,key,left,right,value");
+        assert_eq!(lines[6], "1-2,distance,6,4,9,5");
+        assert_eq!(lines[9], "1-2,similarity,3,3,3,27");
+        assert_eq!(lines.len(), 1 + 6 + 4);
+    }
+
+    #[test]
+    fn test_metric_from_str() {
+        for metric in [Metric::L1, Metric::L2, Metric::Max, Metric::Similarity] {
+            assert_eq!(metric.to_string().parse::<Metric>(), Ok(metric));
+        }
+        assert!("l3".parse::<Metric>().is_err());
+    }
 }
 
 #[cfg(test)]
@@ -113,4 +459,27 @@ mod test_part_2 {
     fn test_full() {
         assert_eq!(31, solve_part2(&get_input()));
     }
+
+    #[test]
+    fn test_try_solve() {
+        assert_eq!(
+            try_solve(&get_input()),
+            Ok(("11".to_string(), "31".to_string()))
+        );
+        assert_eq!(
+            try_solve(&String::from("3   4\n4")),
+            Err(ParseError::at_line(2, "expected 2 location ids, found 1"))
+        );
+        assert_eq!(
+            try_solve(&String::from("3   4\n\n4 5 6")),
+            Err(ParseError::at_line(3, "expected 2 location ids, found 3"))
+        );
+        assert_eq!(
+            try_solve(&String::from("3   4\n3   x")),
+            Err(ParseError::at_line(2, "invalid location id \"x\""))
+        );
+        // Distances that don't fit in a `Num`
+        let input = format!("{}   {}", Num::MIN, Num::MAX);
+        assert_eq!(try_solve(&input).unwrap().0, "4294967295");
+    }
 }
@@ -1,6 +1,17 @@
-use crate::aoc::input::read_input_for_day;
+use crate::aoc::image::{GREEN, RED};
+use crate::aoc::input::{parse_grid, read_input_for_day, ParseError};
+use crate::aoc::memo::Memo;
+use crate::aoc::options::Options;
+use crate::aoc::overlay::{show_overlay_if_requested, Overlay};
+use std::collections::HashSet;
 
 pub fn run() {
+    run_with(&Options::default());
+}
+
+/// `--overlay[=<path>]` shows the trailheads and the tops they reach, see
+/// `show_overlay_if_requested`.
+pub fn run_with(options: &Options) {
     println!("Day 10 Solutions");
     println!("---------------");
     let input = read_input_for_day(10);
@@ -8,20 +19,26 @@ pub fn run() {
     let part2 = solve_part2(&input);
     println!("\tPart1: {part1}");
     println!("\tPart2: {part2}");
+
+    show_overlay_if_requested(options, || trails_overlay(&input));
 }
 
 type Num = i32;
 type Row = Vec<Num>;
 type Topo = Vec<Row>;
-fn parse_input(input: &String) -> Topo {
-    input
-        .split('\n')
-        .map(|line| {
-            line.chars()
-                .map(|c| c.to_digit(10).unwrap() as Num)
-                .collect::<Row>()
+pub fn parse_input(input: &String) -> Result<Topo, ParseError> {
+    parse_grid(input)?
+        .into_iter()
+        .enumerate()
+        .map(|(ix, row)| {
+            row.into_iter()
+                .map(|c| match c.to_digit(10) {
+                    Some(height) => Ok(height as Num),
+                    None => Err(ParseError::at_line(ix + 1, format!("invalid height {c:?}"))),
+                })
+                .collect::<Result<Row, ParseError>>()
         })
-        .collect::<Topo>()
+        .collect::<Result<Topo, ParseError>>()
 }
 
 type Pos = (usize, usize);
@@ -64,11 +81,11 @@ fn neighbors_of(pos: &Pos, topo: &Topo, ignore: &Positions) -> Positions {
         .collect::<Positions>()
 }
 
-fn score(pos: &Pos, topo: &Topo) -> Num {
+fn tops_reachable_from(pos: &Pos, topo: &Topo) -> HashSet<Pos> {
     let mut ignore = Positions::new();
     let mut all_neighbors = vec![(pos.clone(), neighbors_of(pos, topo, &ignore))];
     ignore.push(pos.clone());
-    let mut tops = std::collections::HashSet::<Pos>::new();
+    let mut tops = HashSet::<Pos>::new();
     let mut length = all_neighbors.len();
     while length > 0 {
         let (pos, neighbors) = all_neighbors.remove(0);
@@ -87,28 +104,36 @@ fn score(pos: &Pos, topo: &Topo) -> Num {
         length = all_neighbors.len();
     }
 
-    tops.len() as Num
+    tops
+}
+
+fn score(pos: &Pos, topo: &Topo) -> Num {
+    tops_reachable_from(pos, topo).len() as Num
 }
 
-fn rating(pos: &Pos, topo: &Topo) -> Num {
-    let mut score = 0;
-    let curr = topo[pos.0][pos.1];
-    for npos in neighbors_of(pos, topo, &vec![]) {
-        let neighbor = topo[npos.0][npos.1];
-        if neighbor > curr && (neighbor - curr) == 1 {
-            if neighbor == 9 {
-                score += 1;
-            } else {
-                score += rating(&npos, topo);
+/// The rating of a position only depends on the topo, so one `memo` can be
+/// shared between all trailheads of the same topo.
+fn rating(pos: &Pos, topo: &Topo, memo: &mut Memo<Pos, Num>) -> Num {
+    memo.get_or_compute(*pos, |memo| {
+        let mut score = 0;
+        let curr = topo[pos.0][pos.1];
+        for npos in neighbors_of(pos, topo, &vec![]) {
+            let neighbor = topo[npos.0][npos.1];
+            if neighbor > curr && (neighbor - curr) == 1 {
+                if neighbor == 9 {
+                    score += 1;
+                } else {
+                    score += rating(&npos, topo, memo);
+                }
             }
         }
-    }
 
-    score
+        score
+    })
 }
 
-fn solve_part1(input: &String) -> String {
-    let topo = parse_input(input);
+pub fn solve_part1(input: &String) -> String {
+    let topo = parse_input(input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
     find_trailheads_in(&topo)
         .iter()
         .map(|pos| score(pos, &topo))
@@ -116,15 +141,39 @@ fn solve_part1(input: &String) -> String {
         .to_string()
 }
 
-fn solve_part2(input: &String) -> String {
-    let topo = parse_input(input);
+pub fn solve_part2(input: &String) -> String {
+    let topo = parse_input(input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
+    let mut memo = Memo::new();
     find_trailheads_in(&topo)
         .iter()
-        .map(|pos| rating(pos, &topo))
+        .map(|pos| rating(pos, &topo, &mut memo))
         .fold(0, |acc, score| acc + score)
         .to_string()
 }
 
+fn trails_overlay(input: &String) -> Overlay {
+    let topo = parse_input(input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
+    let trailheads = find_trailheads_in(&topo);
+    let tops = trailheads
+        .iter()
+        .flat_map(|pos| tops_reachable_from(pos, &topo))
+        .collect::<HashSet<Pos>>();
+    let grid = input
+        .split('\n')
+        .map(|line| line.chars().collect::<Vec<char>>())
+        .collect::<Vec<_>>();
+
+    Overlay::new(&grid)
+        .points(trailheads, None, GREEN)
+        .points(tops, None, RED)
+}
+
+/// Both answers, or why `input` can't be solved
+pub fn try_solve(input: &String) -> Result<(String, String), ParseError> {
+    parse_input(input)?;
+    Ok((solve_part1(input), solve_part2(input)))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -159,13 +208,13 @@ mod test {
 
     #[test]
     fn test_parse_input() {
-        let topo = parse_input(&get_input(0));
+        let topo = parse_input(&get_input(0)).unwrap();
         assert_eq!(topo[0], vec![0, 1, 2, 3]);
     }
 
     #[test]
     fn test_find_trailheads() {
-        let topo = parse_input(&get_input(0));
+        let topo = parse_input(&get_input(0)).unwrap();
         let trailheads = find_trailheads_in(&topo);
         assert_eq!(trailheads.len(), 1);
         assert_eq!(trailheads[0], (0, 0));
@@ -173,18 +222,41 @@ mod test {
 
     #[test]
     fn test_score() {
-        let topo = parse_input(&get_input(0));
+        let topo = parse_input(&get_input(0)).unwrap();
         let trailheads = find_trailheads_in(&topo);
         assert_eq!(score(&trailheads[0], &topo), 1);
 
-        let topo = parse_input(&get_input(1));
+        let topo = parse_input(&get_input(1)).unwrap();
         let trailheads = find_trailheads_in(&topo);
         assert_eq!(score(&trailheads[0], &topo), 5);
     }
 
+    #[test]
+    fn test_trails_overlay() {
+        let cells = trails_overlay(&get_input(1)).cells();
+        assert_eq!(cells[0][2], ('0', Some(GREEN)));
+        assert_eq!(cells[0][1], ('9', Some(RED)));
+        assert_eq!(cells[0][0], ('8', None));
+        let count = |color| cells.iter().flatten().filter(|(_, c)| *c == color).count();
+        assert_eq!(count(Some(GREEN)), 9);
+        assert_eq!(count(Some(RED)), 7);
+    }
+
+    #[test]
+    fn test_try_solve() {
+        assert_eq!(
+            try_solve(&String::from("0123\n45x7")),
+            Err(ParseError::at_line(2, "invalid height 'x'"))
+        );
+        assert_eq!(
+            try_solve(&String::from("0123456789")),
+            Ok(("1".to_string(), "1".to_string()))
+        );
+    }
+
     #[test]
     fn test_neighbors_of() {
-        let topo = parse_input(&get_input(0));
+        let topo = parse_input(&get_input(0)).unwrap();
         let empty = vec![];
         for (pos, neighbors) in vec![
             ((0, 0), vec![(0, 1), (1, 0)]),
@@ -198,9 +270,23 @@ mod test {
 
     #[test]
     fn test_rating() {
-        let topo = parse_input(&get_input(2));
+        let topo = parse_input(&get_input(2)).unwrap();
         let trailheads = find_trailheads_in(&topo);
-        assert_eq!(rating(&trailheads[0], &topo), 227);
+        assert_eq!(rating(&trailheads[0], &topo, &mut Memo::new()), 227);
+    }
+
+    #[test]
+    fn test_rating_shares_memo() {
+        let topo = parse_input(&get_input(1)).unwrap();
+        let mut memo = Memo::new();
+        for pos in find_trailheads_in(&topo) {
+            assert_eq!(
+                rating(&pos, &topo, &mut memo),
+                rating(&pos, &topo, &mut Memo::new())
+            );
+        }
+        // Every cell is rated at most once
+        assert!(memo.stats().misses <= 8 * 8);
     }
 
     #[test]
@@ -1,4 +1,6 @@
-use crate::aoc::input::read_input_for_day;
+use crate::aoc::input::{parse_at, read_input_for_day, ParseError};
+use crate::aoc::math::{count_digits, split_digits};
+use crate::aoc::memo::Memo;
 
 pub fn run() {
     println!("Day 11 Solutions");
@@ -13,46 +15,28 @@ pub fn run() {
 type Stone = u64;
 type Stones = Vec<Stone>;
 
-fn parse_input(input: &String) -> Stones {
+/// Stones with up to 12 digits never grow past 18 digits, so they fit in a `Stone`
+const MAX_STONE: Stone = 999_999_999_999;
+
+pub fn parse_input(input: &String) -> Result<Stones, ParseError> {
     input
         .split(' ')
-        .map(|s| s.parse::<Stone>().unwrap())
-        .collect::<Stones>()
-}
-
-fn stone_from(stones: &Stones) -> Stone {
-    let mut pow = 0;
-    let mut new_stone = 0;
-    let stone_10: Stone = 10;
-    for stone in stones.iter().rev() {
-        new_stone += stone * stone_10.pow(pow);
-        pow += 1;
-    }
-
-    new_stone
+        .map(|s| match parse_at::<Stone>(s, 1)? {
+            stone if stone > MAX_STONE => Err(ParseError::at_line(
+                1,
+                format!("stone {stone} is over {MAX_STONE}"),
+            )),
+            stone => Ok(stone),
+        })
+        .collect::<Result<Stones, ParseError>>()
 }
 
 type SplitStone = (Stone, Option<Stone>);
 fn split_stone(stone: &Stone) -> SplitStone {
-    if *stone == 0 {
-        return (0, None);
-    }
-
-    let mut new_stones = Stones::new();
-    let mut temp_stone = *stone;
-    while temp_stone >= 1 {
-        let new_stone = temp_stone % 10;
-        new_stones.push(new_stone);
-        temp_stone /= 10;
-    }
-    let new_stones = new_stones.into_iter().rev().collect::<Stones>();
-    if new_stones.len() % 2 == 0 {
-        let mid = new_stones.len() / 2;
-        let end = new_stones.len();
-        (
-            stone_from(&new_stones[0..mid].to_vec()),
-            Some(stone_from(&new_stones[mid..end].to_vec())),
-        )
+    let n_digits = count_digits(*stone);
+    if *stone != 0 && n_digits.is_multiple_of(2) {
+        let (left, right) = split_digits(*stone, n_digits / 2);
+        (left, Some(right))
     } else {
         (*stone, None)
     }
@@ -66,58 +50,51 @@ fn convert_stone(stone: &Stone) -> Stone {
     }
 }
 
-fn solve_part1(input: &String) -> String {
-    let mut stones = parse_input(input);
-
-    let n_blinks = 25;
-    for _ in 0..n_blinks {
-        let mut new_stones = Stones::new();
-        for stone in stones.iter() {
-            let (left_stone, right) = split_stone(&stone);
-            if let Some(right_stone) = right {
-                new_stones.push(left_stone);
-                new_stones.push(right_stone);
-            } else {
-                let left_stone = convert_stone(&left_stone);
-                new_stones.push(left_stone);
-            }
-        }
+type Count = u64;
 
-        stones = new_stones.clone();
+/// Number of stones that `stone` turns into after `n_blinks`
+fn count_stones_after(
+    stone: Stone,
+    n_blinks: usize,
+    memo: &mut Memo<(Stone, usize), Count>,
+) -> Count {
+    if n_blinks == 0 {
+        return 1;
     }
 
-    stones.len().to_string()
+    memo.get_or_compute((stone, n_blinks), |memo| {
+        let (left_stone, right) = split_stone(&stone);
+        if let Some(right_stone) = right {
+            count_stones_after(left_stone, n_blinks - 1, memo)
+                + count_stones_after(right_stone, n_blinks - 1, memo)
+        } else {
+            count_stones_after(convert_stone(&left_stone), n_blinks - 1, memo)
+        }
+    })
 }
 
-fn solve_part2(_input: &String) -> String {
-    /*
-    let mut stones = parse_input(input);
-
-    let mut map = std::collections::HashMap::<Stone, Stones>::new();
-    let mut stone_count = stones.len();
-    let n_blinks = 75;
+fn count_all_after(stones: &Stones, n_blinks: usize) -> Count {
+    let mut memo = Memo::new();
+    stones
+        .iter()
+        .map(|stone| count_stones_after(*stone, n_blinks, &mut memo))
+        .sum::<Count>()
+}
 
-    for stone in stones.iter() {
-        map.insert(*stone, Stones::new());
-    }
+pub fn solve_part1(input: &String) -> String {
+    let stones = parse_input(input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
+    count_all_after(&stones, 25).to_string()
+}
 
-    for _ in 0..n_blinks {
-        let mapped_stones = map.keys().clone();
-        for stone in mapped_stones {
-            let (left_stone, right) = split_stone(&stone);
-            if let Some(right_stone) = right {
-                map.entry(*stone).and_modify(|s| s.push(left_stone));
-                map.entry(*stone).and_modify(|s| s.push(right_stone));
-            } else {
-                let left_stone = convert_stone(&left_stone);
-                map.entry(*stone).and_modify(|s| s.push(left_stone));
-            }
-        }
-    }
+pub fn solve_part2(input: &String) -> String {
+    let stones = parse_input(input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
+    count_all_after(&stones, 75).to_string()
+}
 
-    stone_count.to_string()
-    */
-    String::new()
+/// Both answers, or why `input` can't be solved
+pub fn try_solve(input: &String) -> Result<(String, String), ParseError> {
+    parse_input(input)?;
+    Ok((solve_part1(input), solve_part2(input)))
 }
 
 #[cfg(test)]
@@ -132,7 +109,7 @@ mod test {
 
     #[test]
     fn test_parse_input() {
-        let stones = parse_input(&get_input(0));
+        let stones = parse_input(&get_input(0)).unwrap();
         assert_eq!(stones, vec![125, 17]);
     }
 
@@ -151,19 +128,30 @@ mod test {
     }
 
     #[test]
-    fn test_stone_from() {
-        for (inpt, expected) in vec![
-            (vec![0], 0),
-            (vec![1, 0], 10),
-            (vec![4, 2], 42),
-            (vec![1, 2, 3], 123),
-        ] {
-            assert_eq!(stone_from(&inpt), expected);
+    fn test_convert_stone() {}
+
+    #[test]
+    fn test_count_stones_after() {
+        let mut memo = Memo::new();
+        for (n_blinks, expected) in [(0, 2), (1, 3), (6, 22), (25, 55312)] {
+            let count = parse_input(&get_input(0))
+                .unwrap()
+                .iter()
+                .map(|stone| count_stones_after(*stone, n_blinks, &mut memo))
+                .sum::<Count>();
+            assert_eq!(count, expected, "failed after {n_blinks} blinks");
         }
     }
 
     #[test]
-    fn test_convert_stone() {}
+    fn test_try_solve() {
+        assert_eq!(
+            try_solve(&String::from("125 x")),
+            Err(ParseError::at_line(1, "invalid value \"x\""))
+        );
+        assert!(try_solve(&String::from("1000000000000")).is_err());
+        assert!(try_solve(&String::from("999999999999 99999999999")).is_ok());
+    }
 
     #[test]
     fn test_full_part1() {
@@ -172,6 +160,6 @@ mod test {
 
     #[test]
     fn test_full_part2() {
-        assert_eq!(solve_part2(&get_input(0)), "0");
+        assert_eq!(solve_part2(&get_input(0)), "65601038650482");
     }
 }
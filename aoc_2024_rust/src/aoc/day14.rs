@@ -1,7 +1,23 @@
-use crate::aoc::input::read_input_for_day;
+use crate::aoc::cycle::brent;
+use crate::aoc::image::{Image, BLACK, GREEN};
+use crate::aoc::input::{parse_at, read_input_for_day, ParseError};
+use crate::aoc::math::{crt, lcm};
+use crate::aoc::options::Options;
+use crate::aoc::ranges::Rect;
+use crate::aoc::recorder::{record_if_requested, FrameRecorder};
 use fancy_regex::Regex;
+use std::fs;
+use std::path::Path;
 
 pub fn run() {
+    run_with(&Options::default());
+}
+
+/// `--dump-ticks=1,2,3` saves the robot map after each of those ticks as an image,
+/// to `--out-dir` (default `images`) as `--format` (`png` or `ppm`, default `png`).
+/// `--record=<path>` records the first `--ticks` (default 100) ticks, see
+/// `record_if_requested`.
+pub fn run_with(options: &Options) {
     println!("Day 14 Solutions");
     println!("---------------");
     let input = read_input_for_day(14);
@@ -9,24 +25,48 @@ pub fn run() {
     let part2 = solve_part2(&input);
     println!("\tPart1: {part1}");
     println!("\tPart2: {part2}");
+
+    let ticks = options.list::<Num>("dump-ticks");
+    if !ticks.is_empty() {
+        let out_dir = Path::new(options.get("out-dir").unwrap_or("images"));
+        let format = options.get("format").unwrap_or("png");
+        let scale = options.parsed::<usize>("scale").unwrap_or(4);
+        let map_dim = XY::new(101, 103);
+        let robots =
+            parse_input(&input, &map_dim).unwrap_or_else(|err| panic!("Invalid input, {err}"));
+        fs::create_dir_all(out_dir).expect("Couldn't create the output directory");
+        for tick in ticks {
+            let path = out_dir.join(format!("day14_tick{tick}.{format}"));
+            let robots = robots.iter().map(|r| r.after(tick)).collect::<Robots>();
+            robot_image(&robots, scale)
+                .save(&path)
+                .unwrap_or_else(|err| panic!("Couldn't save {}: {err}", path.display()));
+            println!("\tSaved {}", path.display());
+        }
+    }
+
+    let n_ticks = options.parsed::<Num>("ticks").unwrap_or(100);
+    record_if_requested(options, "day14", |recorder| {
+        record_ticks(&input, &XY::new(101, 103), n_ticks, recorder)
+    });
 }
 
 type Num = isize;
 
 #[derive(Debug, Eq, PartialEq, Clone)]
-struct XY {
+pub struct XY {
     x: Num,
     y: Num,
 }
 
 impl XY {
-    fn new(x: Num, y: Num) -> Self {
+    pub fn new(x: Num, y: Num) -> Self {
         Self { x: x, y: y }
     }
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
-struct Robot {
+pub struct Robot {
     p: XY,
     v: XY,
     map_dim: XY,
@@ -46,44 +86,61 @@ impl Robot {
     }
 
     fn tick(&mut self) {
-        let mut x = self.p.x + self.v.x;
-        let mut y = self.p.y + self.v.y;
-        if x < 0 {
-            x += self.map_dim.x;
-        } else if x >= self.map_dim.x {
-            x -= self.map_dim.x;
-        }
+        self.p = self.position_after(1);
+    }
 
-        if y < 0 {
-            y += self.map_dim.y;
-        } else if y >= self.map_dim.y {
-            y -= self.map_dim.y;
-        }
-        self.p.x = x;
-        self.p.y = y;
+    fn position_after(&self, n_ticks: Num) -> XY {
+        XY::new(
+            (self.p.x + self.v.x * n_ticks).rem_euclid(self.map_dim.x),
+            (self.p.y + self.v.y * n_ticks).rem_euclid(self.map_dim.y),
+        )
+    }
+
+    fn after(&self, n_ticks: Num) -> Robot {
+        Robot::from_xys(
+            self.position_after(n_ticks),
+            self.v.clone(),
+            self.map_dim.clone(),
+        )
     }
 }
 
 type Robots = Vec<Robot>;
 
-fn parse_input(input: &String, map_dim: &XY) -> Robots {
+/// Keeps `position_after` from overflowing for any tick the solvers look at
+const MAX_SPEED: Num = 1_000_000;
+
+/// At least one robot, all of them starting on the map
+pub fn parse_input(input: &String, map_dim: &XY) -> Result<Robots, ParseError> {
     let mut robots = Robots::new();
     let robots_re = Regex::new(r"(?<=[pv]=)(?<x>-?[0-9]+),(?<y>-?[0-9]+)").unwrap();
-    for line in input.split('\n') {
-        let mut caps = robots_re.captures_iter(line);
-        let cap_p = caps.nth(0).unwrap().unwrap();
-        let cap_v = caps.nth(0).unwrap().unwrap();
-        let px = cap_p["x"].parse::<Num>().unwrap();
-        let py = cap_p["y"].parse::<Num>().unwrap();
-        let vx = cap_v["x"].parse::<Num>().unwrap();
-        let vy = cap_v["y"].parse::<Num>().unwrap();
+    for (ix, line) in input.split('\n').enumerate() {
+        let line_num = ix + 1;
+        let caps = robots_re
+            .captures_iter(line)
+            .take(2)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| ParseError::at_line(line_num, err.to_string()))?;
+        let [cap_p, cap_v] = &caps[..] else {
+            return Err(ParseError::at_line(line_num, "expected p=x,y v=x,y"));
+        };
+        let px = parse_at::<Num>(&cap_p["x"], line_num)?;
+        let py = parse_at::<Num>(&cap_p["y"], line_num)?;
+        let vx = parse_at::<Num>(&cap_v["x"], line_num)?;
+        let vy = parse_at::<Num>(&cap_v["y"], line_num)?;
+        if !(0..map_dim.x).contains(&px) || !(0..map_dim.y).contains(&py) {
+            return Err(ParseError::at_line(line_num, "robot outside of the map"));
+        }
+        if vx.abs() > MAX_SPEED || vy.abs() > MAX_SPEED {
+            return Err(ParseError::at_line(line_num, "robot is too fast"));
+        }
         robots.push(Robot::new(px, py, vx, vy, (*map_dim).clone()));
     }
 
-    robots
+    Ok(robots)
 }
 
-fn render_robot_map(robots: &Robots) {
+fn render_robot_map(robots: &Robots) -> String {
     let mut map: Vec<Vec<String>> = Vec::new();
     let nrows = robots[0].map_dim.y as usize;
     let ncols = robots[0].map_dim.x as usize;
@@ -99,14 +156,36 @@ fn render_robot_map(robots: &Robots) {
         }
         map.push(row);
     }
-    let rendered = map
-        .into_iter()
+    map.into_iter()
         .map(|row| row.into_iter().collect::<Vec<String>>().join(""))
         .collect::<Vec<String>>()
-        .join("\n");
+        .join("\n")
+}
 
-    println!("{}", rendered);
-    println!("");
+fn robot_image(robots: &Robots, scale: usize) -> Image {
+    let map_dim = &robots[0].map_dim;
+    Image::from_points(
+        (map_dim.x as usize, map_dim.y as usize),
+        robots.iter().map(|r| (r.p.x as usize, r.p.y as usize)),
+        GREEN,
+        BLACK,
+        scale,
+    )
+}
+
+fn record_ticks(input: &String, map_dim: &XY, n_ticks: Num, recorder: &mut FrameRecorder) {
+    let dims = (map_dim.x as usize, map_dim.y as usize);
+    let mut robots =
+        parse_input(input, map_dim).unwrap_or_else(|err| panic!("Invalid input, {err}"));
+    for tick in 0..=n_ticks {
+        if tick > 0 {
+            for robot in robots.iter_mut() {
+                robot.tick();
+            }
+        }
+        let points = robots.iter().map(|r| (r.p.x as usize, r.p.y as usize));
+        recorder.push_points(dims, points, GREEN, BLACK);
+    }
 }
 
 fn count_robots_in(loc: XY, robots: &Robots) -> Num {
@@ -116,40 +195,30 @@ fn count_robots_in(loc: XY, robots: &Robots) -> Num {
         .count() as Num
 }
 
-fn calc_locs_in_quadrant(quadrant_ix: usize, map_dim: &XY) -> Vec<XY> {
-    let (xx, yy) = match quadrant_ix {
-        0 => ((0, map_dim.x / 2), (0, map_dim.y / 2)),
-        1 => ((map_dim.x / 2 + 1, map_dim.x), (0, map_dim.y / 2)),
-        2 => ((0, map_dim.x / 2), (map_dim.y / 2 + 1, map_dim.y)),
-        3 => (
-            (map_dim.x / 2 + 1, map_dim.x),
-            (map_dim.y / 2 + 1, map_dim.y),
-        ),
+fn calc_quadrant(quadrant_ix: usize, map_dim: &XY) -> Rect<Num> {
+    let (mid_x, mid_y) = (map_dim.x / 2, map_dim.y / 2);
+    match quadrant_ix {
+        0 => Rect::new(0..mid_x, 0..mid_y),
+        1 => Rect::new(mid_x + 1..map_dim.x, 0..mid_y),
+        2 => Rect::new(0..mid_x, mid_y + 1..map_dim.y),
+        3 => Rect::new(mid_x + 1..map_dim.x, mid_y + 1..map_dim.y),
         _ => panic!("Unsupported quadrant: {quadrant_ix}"),
-    };
-
-    let mut locs = Vec::<XY>::new();
-    for x in xx.0..xx.1 {
-        for y in yy.0..yy.1 {
-            locs.push(XY::new(x, y));
-        }
     }
-
-    locs
 }
 
 fn count_robots_in_quadrant(quadrant_ix: usize, robots: &Robots) -> Num {
     // Assume all robots have the same `map_dim`
     let map_dim = robots[0].map_dim.clone();
-    let locs_in_quadrant = calc_locs_in_quadrant(quadrant_ix, &map_dim);
-    locs_in_quadrant
-        .into_iter()
-        .map(|loc| count_robots_in(loc, robots))
-        .fold(0, |acc, count| acc + count) as Num
+    let quadrant = calc_quadrant(quadrant_ix, &map_dim);
+    robots
+        .iter()
+        .filter(|robot| quadrant.contains((robot.p.x, robot.p.y)))
+        .count() as Num
 }
 
-fn solve_part1_with_map_dim(input: &String, map_dim: &XY) -> String {
-    let mut robots = parse_input(input, &map_dim);
+pub fn solve_part1_with_map_dim(input: &String, map_dim: &XY) -> String {
+    let mut robots =
+        parse_input(input, map_dim).unwrap_or_else(|err| panic!("Invalid input, {err}"));
 
     for _ in 0..100 {
         for robot in robots.iter_mut() {
@@ -163,19 +232,23 @@ fn solve_part1_with_map_dim(input: &String, map_dim: &XY) -> String {
         .to_string()
 }
 
-fn solve_part1(input: &String) -> String {
+pub fn solve_part1(input: &String) -> String {
     let map_dim = XY::new(101, 103);
     solve_part1_with_map_dim(input, &map_dim)
 }
 
 fn find_lines(robots: &Robots, line_len: Num) -> bool {
     let map_dim = robots[0].map_dim.clone();
+    let mut occupied = vec![false; (map_dim.x * map_dim.y) as usize];
+    for robot in robots {
+        occupied[(robot.p.y * map_dim.x + robot.p.x) as usize] = true;
+    }
     let mut xstart = 0;
     let mut ystart = 0;
     let mut count = 0;
     for y in 0..map_dim.y {
         for x in 0..map_dim.x {
-            count = count_robots_in(XY::new(x, y), robots);
+            count = occupied[(y * map_dim.x + x) as usize] as Num;
             if count == 0 {
                 xstart = x;
             }
@@ -194,32 +267,92 @@ fn find_lines(robots: &Robots, line_len: Num) -> bool {
     false
 }
 
-fn solve_part2(input: &String) -> String {
-    let map_dim = XY::new(101, 103);
-    let mut robots = parse_input(input, &map_dim);
+/// Sum of squared deviations of `coord` over all robots, scaled by the robot count
+fn spread_of(robots: &Robots, coord: impl Fn(&XY) -> Num) -> Num {
+    let n = robots.len() as Num;
+    let sum = robots.iter().map(|r| coord(&r.p)).sum::<Num>();
+    let sum_sq = robots.iter().map(|r| coord(&r.p).pow(2)).sum::<Num>();
+    n * sum_sq - sum * sum
+}
 
-    // I originally guessed this, and it was wrong. So, it shouldn't be over 10_000...
-    let n_ticks = 10_000;
-    let mut n_seconds = 0;
-    for i in 1..=n_ticks {
-        for robot in robots.iter_mut() {
-            robot.tick();
-        }
+/// Tick within one `period` at which `coord` is the least spread out
+fn most_clustered_tick(robots: &Robots, period: Num, coord: impl Fn(&XY) -> Num) -> Num {
+    (0..period)
+        .min_by_key(|tick| {
+            let robots = robots.iter().map(|r| r.after(*tick)).collect::<Robots>();
+            spread_of(&robots, &coord)
+        })
+        .unwrap_or(0)
+}
 
-        // Find lines in the map - maybe it's a tree
-        if find_lines(&robots, 10) {
-            render_robot_map(&robots);
-            n_seconds = i;
-            break;
+/// Number of ticks after which all robots are back at their `coord` along one axis
+fn axis_period(robots: &Robots, coord: impl Fn(&XY) -> Num, dim: Num) -> Num {
+    let velocities = robots.iter().map(|r| coord(&r.v)).collect::<Vec<Num>>();
+    let positions = robots.iter().map(|r| coord(&r.p)).collect::<Vec<Num>>();
+    let cycle = brent(positions, |positions| {
+        positions
+            .iter()
+            .zip(velocities.iter())
+            .map(|(p, v)| (p + v).rem_euclid(dim))
+            .collect()
+    });
+
+    cycle.length as Num
+}
+
+/// Part 2 answer when no tick shows the tree
+const NOT_FOUND: &str = "not found";
+
+/// The tick when both axes are most clustered, which is usually the tree
+fn most_clustered_tick_overall(robots: &Robots, map_dim: &XY) -> Option<Num> {
+    // x and y positions repeat independently, so the tree shows up when both
+    // axes are clustered at once.
+    let period_x = axis_period(robots, |xy| xy.x, map_dim.x);
+    let period_y = axis_period(robots, |xy| xy.y, map_dim.y);
+    let tick_x = most_clustered_tick(robots, period_x, |p| p.x);
+    let tick_y = most_clustered_tick(robots, period_y, |p| p.y);
+    let (tick, period) = crt(&[
+        (tick_x as i64, period_x as i64),
+        (tick_y as i64, period_y as i64),
+    ])?;
+
+    Some(if tick == 0 { period } else { tick } as Num)
+}
+
+pub fn solve_part2(input: &String) -> String {
+    let map_dim = XY::new(101, 103);
+    let robots = parse_input(input, &map_dim).unwrap_or_else(|err| panic!("Invalid input, {err}"));
+    if robots.is_empty() {
+        return NOT_FOUND.to_string();
+    }
+
+    let robots_after = |n_ticks: Num| robots.iter().map(|r| r.after(n_ticks)).collect::<Robots>();
+    // Find lines in the map - maybe it's a tree
+    let shows_tree = |n_ticks: &Num| find_lines(&robots_after(*n_ticks), 10);
+    // When the clustering guess is wrong, every map repeats within the lcm
+    // of the dimensions, so checking each tick up to it is enough.
+    let n_seconds = most_clustered_tick_overall(&robots, &map_dim)
+        .filter(shows_tree)
+        .or_else(|| (1..=lcm(map_dim.x as i64, map_dim.y as i64) as Num).find(shows_tree));
+    match n_seconds {
+        Some(n_seconds) => {
+            println!("{}\n", render_robot_map(&robots_after(n_seconds)));
+            n_seconds.to_string()
         }
+        None => NOT_FOUND.to_string(),
     }
+}
 
-    n_seconds.to_string()
+/// Both answers, or why `input` can't be solved
+pub fn try_solve(input: &String) -> Result<(String, String), ParseError> {
+    parse_input(input, &XY::new(101, 103))?;
+    Ok((solve_part1(input), solve_part2(input)))
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::snapshot;
 
     const INPUT: &[&str] = &["
 p=0,4 v=3,-3
@@ -242,7 +375,7 @@ p=9,5 v=-3,-3"];
     #[test]
     fn test_parse_input() {
         let map_dim = XY::new(11, 7);
-        let robots = parse_input(&get_input(0), &map_dim);
+        let robots = parse_input(&get_input(0), &map_dim).unwrap();
         let p0 = XY::new(0, 4);
         let v0 = XY::new(3, -3);
         let p_end = XY::new(9, 5);
@@ -291,6 +424,41 @@ p=9,5 v=-3,-3"];
         assert_eq!(robot0.p.y, 2);
     }
 
+    #[test]
+    fn test_robot_after() {
+        let map_dim = XY::new(11, 7);
+        let robot = Robot::new(2, 4, 2, -3, map_dim.clone());
+        let mut ticked = robot.clone();
+        for n_ticks in 1..=100 {
+            ticked.tick();
+            assert_eq!(robot.after(n_ticks), ticked, "failed after {n_ticks} ticks");
+        }
+    }
+
+    #[test]
+    fn test_most_clustered_tick() {
+        // Robots line up on x == 5 after 3 ticks
+        let map_dim = XY::new(11, 7);
+        let robots = vec![
+            Robot::new(2, 0, 1, 1, map_dim.clone()),
+            Robot::new(8, 1, -1, 2, map_dim.clone()),
+            Robot::new(3, 2, -3, 0, map_dim.clone()),
+        ];
+        assert_eq!(most_clustered_tick(&robots, map_dim.x, |p| p.x), 3);
+    }
+
+    #[test]
+    fn test_axis_period() {
+        let map_dim = XY::new(11, 7);
+        let robots = parse_input(&get_input(0), &map_dim).unwrap();
+        assert_eq!(axis_period(&robots, |xy| xy.x, map_dim.x), 11);
+        assert_eq!(axis_period(&robots, |xy| xy.y, map_dim.y), 7);
+
+        // Robots that don't move along an axis never leave their spot on it
+        let robots = vec![Robot::new(1, 2, 0, 2, map_dim.clone())];
+        assert_eq!(axis_period(&robots, |xy| xy.x, map_dim.x), 1);
+    }
+
     #[test]
     fn test_robot_counts() {
         let map_dim = XY::new(11, 7);
@@ -304,35 +472,88 @@ p=9,5 v=-3,-3"];
 
         assert_eq!(count_robots_in(XY::new(4, 1), &robots), 1);
 
-        let mut robots = parse_input(&get_input(0), &map_dim);
-        render_robot_map(&robots);
+        let mut robots = parse_input(&get_input(0), &map_dim).unwrap();
+        snapshot::assert_text("day14_robot_map", &render_robot_map(&robots));
 
         for _ in 0..100 {
             for robot in robots.iter_mut() {
                 robot.tick();
             }
         }
-        render_robot_map(&robots);
+        snapshot::assert_text("day14_robot_map_after_100", &render_robot_map(&robots));
         assert_eq!(count_robots_in(XY::new(0, 2), &robots), 1);
         assert_eq!(count_robots_in(XY::new(6, 0), &robots), 2);
     }
 
+    #[test]
+    fn test_robot_image() {
+        let map_dim = XY::new(11, 7);
+        let robots = parse_input(&get_input(0), &map_dim).unwrap();
+        let image = robot_image(&robots, 2);
+        assert_eq!((image.width(), image.height()), (22, 14));
+        // p=0,4
+        assert_eq!(image.get((1, 9)), GREEN);
+        assert_eq!(image.get((3, 3)), BLACK);
+        snapshot::assert_image("day14_robot_image", &image);
+    }
+
+    #[test]
+    fn test_record_ticks() {
+        let map_dim = XY::new(11, 7);
+        let mut recorder = FrameRecorder::new(10, 1);
+        record_ticks(&get_input(0), &map_dim, 100, &mut recorder);
+        assert_eq!(recorder.frames().len(), 11);
+        // After 100 ticks there are 2 robots at 6,0
+        assert_eq!(recorder.frames()[10].get((6, 0)), GREEN);
+    }
+
     #[test]
     fn test_robot_quadrant_count() {
         let map_dim = XY::new(11, 7);
-        let mut robots = parse_input(&get_input(0), &map_dim);
+        let mut robots = parse_input(&get_input(0), &map_dim).unwrap();
         for _ in 0..100 {
             for robot in robots.iter_mut() {
                 robot.tick();
             }
         }
-        render_robot_map(&robots);
+        snapshot::assert_text("day14_robot_map_after_100", &render_robot_map(&robots));
         assert_eq!(count_robots_in_quadrant(0, &robots), 1, "quadrant 0 failed");
         assert_eq!(count_robots_in_quadrant(1, &robots), 3, "quadrant 1 failed");
         assert_eq!(count_robots_in_quadrant(2, &robots), 4, "quadrant 2 failed");
         assert_eq!(count_robots_in_quadrant(3, &robots), 1, "quadrant 3 failed");
     }
 
+    #[test]
+    fn test_calc_quadrant() {
+        let map_dim = XY::new(11, 7);
+        let quadrants = (0..=3)
+            .map(|ix| calc_quadrant(ix, &map_dim))
+            .collect::<Vec<Rect<Num>>>();
+        for quadrant in quadrants.iter() {
+            assert_eq!(quadrant.area(), 5 * 3);
+            // The middle row and column aren't in any quadrant
+            assert!(!quadrant.contains((5, 1)));
+            assert!(!quadrant.contains((1, 3)));
+        }
+        assert!(quadrants[0].contains((0, 0)));
+        assert!(quadrants[3].contains((10, 6)));
+    }
+
+    #[test]
+    fn test_try_solve() {
+        assert_eq!(
+            try_solve(&String::from("p=0,4 v=3,-3\np=6,3")),
+            Err(ParseError::at_line(2, "expected p=x,y v=x,y"))
+        );
+        assert_eq!(
+            try_solve(&String::from("p=101,0 v=1,1")),
+            Err(ParseError::at_line(1, "robot outside of the map"))
+        );
+        assert!(try_solve(&String::from("p=1,0 v=1,99999999999")).is_err());
+        assert!(try_solve(&String::from("p=1,0 v=1,999999999999999999999")).is_err());
+        assert_eq!(try_solve(&String::from("p=1,0 v=1,1")).unwrap().0, "0");
+    }
+
     #[test]
     fn test_full_part1() {
         assert_eq!(
@@ -343,6 +564,15 @@ p=9,5 v=-3,-3"];
 
     #[test]
     fn test_full_part2() {
-        assert_eq!(solve_part2(&get_input(0)), "");
+        assert_eq!(solve_part2(&get_input(0)), NOT_FOUND);
+    }
+
+    #[test]
+    fn test_part2_finds_lines() {
+        // Robots standing still in a long row show a line from the first tick
+        let input = (1..13)
+            .map(|x| format!("p={x},5 v=0,0"))
+            .collect::<Vec<_>>();
+        assert_eq!(solve_part2(&input.join("\n")), "1");
     }
 }
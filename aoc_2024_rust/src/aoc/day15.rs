@@ -1,6 +1,18 @@
-use crate::aoc::input::read_input_for_day;
+use crate::aoc::image::{Palette, BLACK, GREY, RED, YELLOW};
+use crate::aoc::input::{parse_grid, read_input_for_day, ParseError};
+use crate::aoc::options::Options;
+use crate::aoc::recorder::{record_if_requested, FrameRecorder};
+use crate::aoc::viewer::{run_in_terminal, Viewer};
+use std::time::Duration;
 
 pub fn run() {
+    run_with(&Options::default());
+}
+
+/// `--record=<path>` records the robot pushing boxes around, in the scaled up
+/// warehouse with `--part=2`. See `record_if_requested`.
+/// `--view` steps through the moves in the terminal instead, see `Viewer`.
+pub fn run_with(options: &Options) {
     println!("Day 15 Solutions");
     println!("---------------");
     let input = read_input_for_day(15);
@@ -8,11 +20,21 @@ pub fn run() {
     let part2 = solve_part2(&input);
     println!("\tPart1: {part1}");
     println!("\tPart2: {part2}");
+
+    let part = options.part(1);
+    if options.flag("view") {
+        let delay = Duration::from_millis(options.parsed("delay").unwrap_or(100));
+        view_moves(&input, part, delay).expect("Couldn't run the viewer");
+        return;
+    }
+    record_if_requested(options, "day15", |recorder| {
+        record_moves(&input, part, recorder)
+    });
 }
 
 type Num = isize;
 #[derive(Debug, Clone, Eq, PartialEq)]
-struct Thing {
+pub struct Thing {
     c: char,
     i: Num,
     j: Num,
@@ -69,18 +91,37 @@ fn find_things_in(grid: &Grid, elem: char) -> Things {
         .collect::<Things>()
 }
 
-fn parse_input(input: &String) -> (Thing, Grid, Moves) {
-    let grid_str = input.split("\n\n").nth(0).unwrap();
-    let move_str = input.split("\n\n").nth(1).unwrap();
-    let grid = grid_str
-        .split('\n')
-        .map(|row| row.chars().collect::<Row>())
-        .collect::<Grid>();
-    let robot = find_things_in(&grid, '@')[0].clone();
+pub fn parse_input(input: &String) -> Result<(Thing, Grid, Moves), ParseError> {
+    let (grid_str, move_str) = input
+        .split_once("\n\n")
+        .ok_or_else(|| ParseError::new("expected a map and moves separated by a blank line"))?;
+    let grid = parse_grid(grid_str)?;
+    for (i, row) in grid.iter().enumerate() {
+        if let Some(c) = row.iter().find(|c| !"#.O@".contains(**c)) {
+            return Err(ParseError::at_line(i + 1, format!("unexpected cell {c:?}")));
+        }
+    }
+    let robots = find_things_in(&grid, '@');
+    if robots.len() != 1 {
+        return Err(ParseError::new(format!(
+            "expected one robot, found {}",
+            robots.len()
+        )));
+    }
 
-    let moves = move_str.chars().filter(|m| *m != '\n').collect::<Moves>();
+    let first_move_line = grid.len() + 2;
+    let mut moves = Moves::new();
+    for (i, line) in move_str.lines().enumerate() {
+        if let Some(m) = line.chars().find(|m| !"^v<>".contains(*m)) {
+            return Err(ParseError::at_line(
+                first_move_line + i,
+                format!("unknown move {m:?}"),
+            ));
+        }
+        moves.extend(line.chars());
+    }
 
-    (robot, grid, moves)
+    Ok((robots[0].clone(), grid, moves))
 }
 
 fn in_bounds(loc: Loc, grid: &Grid) -> bool {
@@ -168,8 +209,9 @@ fn get_thing_at(loc: Loc, grid: &Grid) -> Thing {
     Thing::new(grid[loc0][loc1], loc.0, loc.1)
 }
 
-fn solve_part1(input: &String) -> String {
-    let (mut robot, mut grid, moves) = parse_input(input);
+pub fn solve_part1(input: &String) -> String {
+    let (mut robot, mut grid, moves) =
+        parse_input(input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
 
     for direction in moves {
         if let Some(robot_grid) = move_thing(&robot, &grid, direction) {
@@ -216,8 +258,8 @@ fn scale_grid(grid: &Grid) -> Grid {
     new_grid
 }
 
-fn solve_part2(input: &String) -> String {
-    let (_, grid, moves) = parse_input(input);
+pub fn solve_part2(input: &String) -> String {
+    let (_, grid, moves) = parse_input(input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
     let mut grid = scale_grid(&grid);
     let mut robot = find_things_in(&grid, '@')[0].clone();
 
@@ -235,10 +277,89 @@ fn solve_part2(input: &String) -> String {
         .to_string()
 }
 
+fn record_moves(input: &String, part: u8, recorder: &mut FrameRecorder) {
+    let palette = Palette::new(BLACK)
+        .with('#', GREY)
+        .with('O', YELLOW)
+        .with('[', YELLOW)
+        .with(']', YELLOW)
+        .with('@', RED);
+    let (mut robot, mut grid, moves) =
+        parse_input(input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
+    let move_thing = if part == 2 {
+        grid = scale_grid(&grid);
+        robot = find_things_in(&grid, '@')[0].clone();
+        move_thing_2
+    } else {
+        move_thing
+    };
+
+    recorder.push_grid(&grid, &palette);
+    for direction in moves {
+        if let Some(robot_grid) = move_thing(&robot, &grid, direction) {
+            (robot, grid) = robot_grid;
+        }
+        recorder.push_grid(&grid, &palette);
+    }
+}
+
+type MoveState = (Thing, Grid, usize);
+
+fn moves_viewer(
+    input: &String,
+    part: u8,
+) -> Viewer<MoveState, impl Fn(&MoveState) -> Option<MoveState>, impl Fn(&MoveState) -> String> {
+    let (mut robot, mut grid, moves) =
+        parse_input(input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
+    let move_thing = if part == 2 {
+        grid = scale_grid(&grid);
+        robot = find_things_in(&grid, '@')[0].clone();
+        move_thing_2
+    } else {
+        move_thing
+    };
+    let render_moves = moves.clone();
+
+    Viewer::new(
+        (robot, grid, 0),
+        move |(robot, grid, ix): &MoveState| {
+            let direction = *moves.get(*ix)?;
+            Some(match move_thing(robot, grid, direction) {
+                Some((robot, grid)) => (robot, grid, ix + 1),
+                None => (robot.clone(), grid.clone(), ix + 1),
+            })
+        },
+        move |(_, grid, ix): &MoveState| {
+            let next_move = render_moves
+                .get(*ix)
+                .map_or("none".to_string(), |m| m.to_string());
+            format!("{}\n\nnext move: {next_move}", render_grid(grid))
+        },
+    )
+}
+
+fn view_moves(input: &String, part: u8, delay: Duration) -> std::io::Result<()> {
+    run_in_terminal(&mut moves_viewer(input, part), delay)
+}
+
+fn render_grid(grid: &Grid) -> String {
+    grid.iter()
+        .map(|row| row.iter().collect::<String>())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Both answers, or why `input` can't be solved
+pub fn try_solve(input: &String) -> Result<(String, String), ParseError> {
+    parse_input(input)?;
+    Ok((solve_part1(input), solve_part2(input)))
+}
+
 #[cfg(test)]
 mod test {
-    use itertools::Itertools;
     use super::*;
+    use crate::aoc::snapshot;
+    use crate::aoc::viewer::HeadlessBackend;
 
     const INPUT: &[&str] = &[
         "
@@ -290,24 +411,44 @@ v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^",
         String::from(INPUT[ix].trim())
     }
 
-    fn render_grid(grid: &Grid) {
-        println!("");
-        println!(
-            "{}",
-            grid.into_iter()
-                .map(|row| row.into_iter().collect::<String>())
-                .join("\n")
-        );
-    }
-
     #[test]
     fn test_parse_input() {
-        let (robot, grid, moves) = parse_input(&get_input(1));
+        let (robot, grid, moves) = parse_input(&get_input(1)).unwrap();
         assert_eq!(robot, Thing::new('@', 2, 2));
         assert_eq!(grid[0][0], '#');
         assert_eq!(moves[0], '<');
     }
 
+    #[test]
+    fn test_try_solve() {
+        assert_eq!(
+            try_solve(&String::from("#@#")),
+            Err(ParseError::new(
+                "expected a map and moves separated by a blank line"
+            ))
+        );
+        assert_eq!(
+            try_solve(&String::from("#@.#\n#..#\n\n<>")),
+            Ok(("0".to_string(), "0".to_string()))
+        );
+        assert_eq!(
+            try_solve(&String::from("#@.#\n#.#\n\n<>")),
+            Err(ParseError::at_line(2, "expected 4 cells, found 3"))
+        );
+        assert_eq!(
+            try_solve(&String::from("#@.#\n#.x#\n\n<>")),
+            Err(ParseError::at_line(2, "unexpected cell 'x'"))
+        );
+        assert_eq!(
+            try_solve(&String::from("#@.#\n#@.#\n\n<>")),
+            Err(ParseError::new("expected one robot, found 2"))
+        );
+        assert_eq!(
+            try_solve(&String::from("#@.#\n#..#\n\n<>\n<x")),
+            Err(ParseError::at_line(5, "unknown move 'x'"))
+        );
+    }
+
     #[test]
     fn test_move_stuff() {
         // Move right
@@ -326,7 +467,7 @@ v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^",
         let loc = move_loc((1, 1), '^');
         assert_eq!(loc, (0, 1), "failed moving up");
 
-        let (robot, grid, _) = parse_input(&get_input(1));
+        let (robot, grid, _) = parse_input(&get_input(1)).unwrap();
         let robot_grid = move_thing(&robot, &grid, '>');
         assert!(robot_grid.is_some());
         let Some((robot, grid)) = robot_grid else {
@@ -339,7 +480,7 @@ v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^",
 
     #[test]
     fn test_push_stuff() {
-        let (robot, grid, _) = parse_input(&get_input(1));
+        let (robot, grid, _) = parse_input(&get_input(1)).unwrap();
         assert!(move_thing(&robot, &grid, '<').is_none());
 
         let robot_grid = move_thing(&robot, &grid, '^');
@@ -381,11 +522,11 @@ v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^",
 
     #[test]
     fn test_push_stuff_2() {
-        let (_, grid, _) = parse_input(&get_input(2));
+        let (_, grid, _) = parse_input(&get_input(2)).unwrap();
         let mut grid = scale_grid(&grid);
         let mut robot = find_things_in(&grid, '@')[0].clone();
 
-        render_grid(&grid);
+        let mut grids = vec![render_grid(&grid)];
         let robot_grid = move_thing_2(&robot, &grid, '<');
         let Some((r, g)) = robot_grid else {
             panic!("Unexpected")
@@ -398,7 +539,7 @@ v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^",
         let loc = (robot.i - 1, robot.j);
         let w = Thing::new('.', loc.0, loc.1);
         assert_eq!(get_thing_at(loc, &grid), w);
-        render_grid(&grid);
+        grids.push(render_grid(&grid));
 
         for direction in vec!['v', 'v', '<', '<', '^'] {
             let Some((r, g)) = move_thing_2(&robot, &grid, direction) else {
@@ -406,10 +547,10 @@ v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^",
             };
             robot = r;
             grid = g;
-            render_grid(&grid);
+            grids.push(render_grid(&grid));
         }
         assert!(move_thing_2(&robot, &grid, '^').is_none());
-        render_grid(&grid);
+        grids.push(render_grid(&grid));
         for direction in vec!['<', '<', '^', '^'] {
             let Some((r, g)) = move_thing_2(&robot, &grid, direction) else {
                 panic!("Unexpected")
@@ -417,15 +558,62 @@ v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^",
 
             robot = r;
             grid = g;
-            render_grid(&grid);
+            grids.push(render_grid(&grid));
         }
         assert!(move_thing_2(&robot, &grid, '^').is_none());
-        render_grid(&grid);
+        grids.push(render_grid(&grid));
+        snapshot::assert_text("day15_push_stuff_2", &grids.join("\n\n"));
+    }
+
+    #[test]
+    fn test_record_moves() {
+        let mut recorder = FrameRecorder::new(5, 1);
+        record_moves(&get_input(1), 1, &mut recorder);
+        // 15 moves and the start, every 5th kept
+        assert_eq!(recorder.frames().len(), 4);
+        assert_eq!(recorder.frames()[0].get((2, 2)), RED);
+        assert_eq!(recorder.frames()[0].get((0, 0)), GREY);
+
+        let mut recorder = FrameRecorder::new(1, 1);
+        record_moves(&get_input(2), 2, &mut recorder);
+        assert_eq!(recorder.frames()[0].width(), 14);
+        assert_eq!(recorder.frames()[0].get((6, 3)), YELLOW);
+    }
+
+    #[test]
+    fn test_moves_viewer() {
+        let mut viewer = moves_viewer(&get_input(1), 1);
+        assert_eq!(viewer.current().0, Thing::new('@', 2, 2));
+        // '<' into a wall, then '^'
+        viewer.jump(2);
+        assert_eq!(viewer.current().0, Thing::new('@', 1, 2));
+        viewer.back();
+        assert_eq!(viewer.current().0, Thing::new('@', 2, 2));
+
+        viewer.jump(1000);
+        assert_eq!(viewer.position(), 15);
+        let (_, grid, _) = viewer.current();
+        assert_eq!(
+            render_grid(grid),
+            "\
+########
+#....OO#
+##.....#
+#.....O#
+#.#O@..#
+#...O..#
+#...O..#
+########"
+        );
+
+        let mut backend = HeadlessBackend::default();
+        viewer.draw(&mut backend).unwrap();
+        assert!(backend.frames[0].ends_with("next move: none"));
     }
 
     #[test]
     fn test_scale_grid() {
-        let (_, grid, _) = parse_input(&get_input(2));
+        let (_, grid, _) = parse_input(&get_input(2)).unwrap();
         let grid = scale_grid(&grid);
         assert_eq!(grid.len(), 7);
         assert_eq!(grid[0].len(), 14);
@@ -1,6 +1,16 @@
-use crate::aoc::input::read_input_for_day;
+use crate::aoc::input::{parse_at, read_input_for_day, ParseError};
+use crate::aoc::options::Options;
+use std::fmt;
+use std::str::FromStr;
 
 pub fn run() {
+    run_with(&Options::default());
+}
+
+/// Rule options (see `SafetyRules::from_options`) count the safe reports under
+/// those rules too. `--diagnose` prints why each report is safe or not under
+/// them, see `diagnose`.
+pub fn run_with(options: &Options) {
     println!("Day 2 Solutions");
     println!("---------------");
     let input = read_input_for_day(2);
@@ -8,22 +18,55 @@ pub fn run() {
     let part2 = solve_part2(&input);
     println!("\tPart1: {part1}");
     println!("\tPart2: {part2}");
+
+    let rules = SafetyRules::from_options(options);
+    let reports = parse_input(&input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
+    if rules != SafetyRules::default() {
+        let n_safe = reports.iter().filter(|r| is_safe(r, &rules)).count();
+        let n_tolerated = reports
+            .iter()
+            .filter(|r| is_safe_tolerant(r, &rules))
+            .count();
+        println!("\tSafe with the given rules: {n_safe}, {n_tolerated} with the Problem Dampener");
+    }
+    if options.flag("diagnose") {
+        for (ix, report) in reports.iter().enumerate() {
+            let levels = report
+                .iter()
+                .map(|level| level.to_string())
+                .collect::<Vec<String>>()
+                .join(" ");
+            println!("{}: {levels}: {}", ix + 1, diagnose(report, &rules));
+        }
+    }
 }
 
-fn solve_part1(input: &String) -> String {
-    let reports = parse_input(input);
+pub fn solve_part1(input: &String) -> String {
+    let reports = parse_input(input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
     reports
         .iter()
-        .map(|report| if is_safe(report) { 1 } else { 0 })
+        .map(|report| {
+            if is_safe(report, &SafetyRules::default()) {
+                1
+            } else {
+                0
+            }
+        })
         .sum::<Num>()
         .to_string()
 }
 
-fn solve_part2(input: &String) -> String {
-    let reports = parse_input(input);
+pub fn solve_part2(input: &String) -> String {
+    let reports = parse_input(input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
     reports
         .iter()
-        .map(|report| if is_safe_tolerant(report) { 1 } else { 0 })
+        .map(|report| {
+            if is_safe_tolerant(report, &SafetyRules::default()) {
+                1
+            } else {
+                0
+            }
+        })
         .sum::<Num>()
         .to_string()
 }
@@ -32,56 +75,309 @@ type Num = i32;
 type Report = Vec<Num>;
 type Reports = Vec<Report>;
 
-fn parse_input(input: &String) -> Reports {
+pub fn parse_input(input: &String) -> Result<Reports, ParseError> {
     input
         .split('\n')
-        .map(|line| {
+        .enumerate()
+        .map(|(ix, line)| {
             line.split(' ')
-                .map(|s| s.parse::<Num>().unwrap())
-                .collect::<Report>()
+                .map(|s| parse_at::<Num>(s, ix + 1))
+                .collect::<Result<Report, ParseError>>()
         })
-        .collect::<Reports>()
+        .collect::<Result<Reports, ParseError>>()
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Directions {
+    Increasing,
+    Decreasing,
+    Either,
 }
 
-fn all_decreasing(report: &Report) -> bool {
-    report
-        .windows(2)
-        .filter(|levels| levels[0] > levels[1])
-        .count()
-        == report.len() - 1
+impl FromStr for Directions {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "increasing" => Ok(Directions::Increasing),
+            "decreasing" => Ok(Directions::Decreasing),
+            "either" => Ok(Directions::Either),
+            _ => Err(format!("Unknown directions {s:?}")),
+        }
+    }
 }
 
-fn all_increasing(report: &Report) -> bool {
-    report
-        .windows(2)
-        .filter(|levels| levels[0] < levels[1])
-        .count()
-        == report.len() - 1
+/// What makes a report safe. The default is the puzzle's: levels all
+/// increasing or all decreasing by 1 to 3, with one bad level tolerated.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SafetyRules {
+    pub min_step: Num,
+    pub max_step: Num,
+    pub directions: Directions,
+    /// Whether a level can repeat the one before it, whatever the direction
+    pub allow_equal: bool,
+    /// How many levels the Problem Dampener can remove
+    pub tolerance: usize,
+}
+
+impl Default for SafetyRules {
+    fn default() -> Self {
+        Self {
+            min_step: 1,
+            max_step: 3,
+            directions: Directions::Either,
+            allow_equal: false,
+            tolerance: 1,
+        }
+    }
+}
+
+impl SafetyRules {
+    /// The default rules, changed by `--min-step=<n>`, `--max-step=<n>`,
+    /// `--directions=increasing|decreasing|either`, `--allow-equal[=true|false]`
+    /// and `--tolerance=<n>`. Panics unless `0 <= min-step <= max-step`.
+    pub fn from_options(options: &Options) -> Self {
+        let default = Self::default();
+        let rules = Self {
+            min_step: options.parsed("min-step").unwrap_or(default.min_step),
+            max_step: options.parsed("max-step").unwrap_or(default.max_step),
+            directions: options.parsed("directions").unwrap_or(default.directions),
+            allow_equal: options.switch("allow-equal"),
+            tolerance: options.parsed("tolerance").unwrap_or(default.tolerance),
+        };
+        if !(0 <= rules.min_step && rules.min_step <= rules.max_step) {
+            panic!(
+                "Invalid steps, need 0 <= --min-step <= --max-step but got {} and {}",
+                rules.min_step, rules.max_step
+            );
+        }
+
+        rules
+    }
+
+    /// `true` for increasing
+    fn allowed_directions(&self) -> Vec<bool> {
+        match self.directions {
+            Directions::Increasing => vec![true],
+            Directions::Decreasing => vec![false],
+            Directions::Either => vec![true, false],
+        }
+    }
+
+    fn is_safe_step(&self, from: Num, to: Num, increasing: bool) -> bool {
+        if from == to {
+            return self.allow_equal;
+        }
+        let step = from.abs_diff(to);
+        (from < to) == increasing
+            && step >= self.min_step.unsigned_abs()
+            && step <= self.max_step.unsigned_abs()
+    }
+}
+
+fn is_safe(report: &Report, rules: &SafetyRules) -> bool {
+    rules.allowed_directions().into_iter().any(|increasing| {
+        report
+            .windows(2)
+            .all(|levels| rules.is_safe_step(levels[0], levels[1], increasing))
+    })
+}
+
+fn is_safe_tolerant(report: &Report, rules: &SafetyRules) -> bool {
+    removals_to_make_safe(report, rules).is_some()
+}
+
+/// Indices of the fewest levels to remove from `report` to make it safe, when
+/// that's at most `rules.tolerance`. Each level is only checked against the
+/// `tolerance + 1` levels before it, so this takes O(n * tolerance).
+pub fn removals_to_make_safe(report: &[Num], rules: &SafetyRules) -> Option<Vec<usize>> {
+    rules
+        .allowed_directions()
+        .into_iter()
+        .filter_map(|increasing| removals_in_direction(report, rules, increasing))
+        .min_by_key(|removals| removals.len())
+}
+
+fn removals_in_direction(
+    report: &[Num],
+    rules: &SafetyRules,
+    increasing: bool,
+) -> Option<Vec<usize>> {
+    let max_removals = rules.tolerance;
+    let n = report.len();
+    if n == 0 {
+        return Some(Vec::new());
+    }
+
+    // Fewest removals before level j when keeping it, and the kept level before it
+    let mut best = vec![None::<(usize, Option<usize>)>; n];
+    for j in 0..n {
+        if j <= max_removals {
+            best[j] = Some((j, None));
+        }
+        for i in j.saturating_sub(max_removals + 1)..j {
+            let Some((removed, _)) = best[i] else {
+                continue;
+            };
+            let removed = removed + j - i - 1;
+            if removed <= max_removals
+                && rules.is_safe_step(report[i], report[j], increasing)
+                && best[j].is_none_or(|(fewest, _)| removed < fewest)
+            {
+                best[j] = Some((removed, Some(i)));
+            }
+        }
+    }
+
+    // The last kept level, the ones after it are removed too
+    let (last, _) = (0..n)
+        .filter_map(|j| Some((j, best[j]?.0 + n - 1 - j)))
+        .filter(|(_, removed)| *removed <= max_removals)
+        .min_by_key(|(_, removed)| *removed)?;
+    let mut kept = vec![false; n];
+    let mut level = Some(last);
+    while let Some(j) = level {
+        kept[j] = true;
+        level = best[j].and_then(|(_, before)| before);
+    }
+
+    Some((0..n).filter(|j| !kept[*j]).collect())
+}
+
+/// A step between the levels at `index` and `index + 1` that breaks a rule
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Violation {
+    /// The step goes against the direction of the report, or stays level when
+    /// that isn't allowed
+    Direction {
+        index: usize,
+        levels: (Num, Num),
+        increasing: bool,
+    },
+    /// The step is smaller than `min` or bigger than `max`
+    Gap {
+        index: usize,
+        levels: (Num, Num),
+        min: Num,
+        max: Num,
+    },
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Verdict {
+    Safe,
+    /// Safe once the levels at these indices are removed
+    SafeWithout(Vec<usize>),
+    Unsafe,
+}
+
+/// Every rule a report breaks, and whether the Problem Dampener can fix it
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Diagnosis {
+    pub violations: Vec<Violation>,
+    pub verdict: Verdict,
+}
+
+/// Why `report` is safe or not under `rules`. When either direction is
+/// allowed, its direction is the one most of its steps take, increasing on a tie.
+pub fn diagnose(report: &Report, rules: &SafetyRules) -> Diagnosis {
+    let increasing = match rules.directions {
+        Directions::Increasing => true,
+        Directions::Decreasing => false,
+        Directions::Either => {
+            let n_increasing = report.windows(2).filter(|l| l[0] < l[1]).count();
+            let n_decreasing = report.windows(2).filter(|l| l[0] > l[1]).count();
+            n_increasing >= n_decreasing
+        }
+    };
+
+    let mut violations = Vec::new();
+    for (index, l) in report.windows(2).enumerate() {
+        let levels = (l[0], l[1]);
+        let level = l[0] == l[1];
+        if (level && !rules.allow_equal) || (!level && (l[0] < l[1]) != increasing) {
+            violations.push(Violation::Direction {
+                index,
+                levels,
+                increasing,
+            });
+        }
+        let step = l[0].abs_diff(l[1]);
+        if !level && (step < rules.min_step.unsigned_abs() || step > rules.max_step.unsigned_abs())
+        {
+            violations.push(Violation::Gap {
+                index,
+                levels,
+                min: rules.min_step,
+                max: rules.max_step,
+            });
+        }
+    }
+
+    let verdict = if violations.is_empty() {
+        Verdict::Safe
+    } else {
+        removals_to_make_safe(report, rules).map_or(Verdict::Unsafe, Verdict::SafeWithout)
+    };
+
+    Diagnosis {
+        violations,
+        verdict,
+    }
 }
 
-fn all_respect_max_diff(report: &Report, max_diff: Num) -> bool {
-    report
-        .windows(2)
-        .filter(|levels| Num::abs(levels[0] - levels[1]) <= max_diff)
-        .count()
-        == report.len() - 1
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Violation::Direction {
+                index,
+                levels: (a, b),
+                increasing,
+            } => {
+                let direction = if *increasing { "increase" } else { "decrease" };
+                write!(f, "{a} -> {b} at {index} doesn't {direction}")
+            }
+            Violation::Gap {
+                index,
+                levels: (a, b),
+                min,
+                max,
+            } => write!(f, "{a} -> {b} at {index} doesn't change by {min} to {max}"),
+        }
+    }
 }
 
-fn is_safe(report: &Report) -> bool {
-    (all_decreasing(report) || all_increasing(report)) && all_respect_max_diff(report, 3)
+impl fmt::Display for Diagnosis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.verdict {
+            Verdict::Safe => write!(f, "safe")?,
+            Verdict::SafeWithout(indices) => {
+                let indices = indices
+                    .iter()
+                    .map(|index| index.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                write!(f, "safe without the levels at {indices}")?
+            }
+            Verdict::Unsafe => write!(f, "unsafe")?,
+        }
+        for violation in self.violations.iter() {
+            write!(f, "; {violation}")?;
+        }
+        Ok(())
+    }
 }
 
-fn is_safe_tolerant(report: &Report) -> bool {
-    (0..report.len())
-        .map(|i| [&report[..i], &report[i + 1..]].concat())
-        .filter(is_safe)
-        .count()
-        > 0
+/// Both answers, or why `input` can't be solved
+pub fn try_solve(input: &String) -> Result<(String, String), ParseError> {
+    parse_input(input)?;
+    Ok((solve_part1(input), solve_part2(input)))
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use proptest::prelude::*;
 
     const INPUT: [&str; 1] = ["
 7 6 4 2 1
@@ -98,35 +394,173 @@ mod test {
 
     #[test]
     fn test_parse_input() {
-        let parsed = parse_input(&get_input(0));
+        let parsed = parse_input(&get_input(0)).unwrap();
         assert_eq!(parsed[0], vec![7, 6, 4, 2, 1]);
     }
 
+    fn rules(directions: Directions) -> SafetyRules {
+        SafetyRules {
+            directions,
+            ..SafetyRules::default()
+        }
+    }
+
     #[test]
-    fn test_all_decreasing() {
+    fn test_is_safe_decreasing() {
         let report = vec![7, 6, 4, 2, 1];
-        assert!(all_decreasing(&report));
+        assert!(is_safe(&report, &rules(Directions::Decreasing)));
+        assert!(!is_safe(&report, &rules(Directions::Increasing)));
 
         let report = vec![7, 6, 4, 2, 4];
-        assert!(!all_decreasing(&report));
+        assert!(!is_safe(&report, &rules(Directions::Decreasing)));
     }
 
     #[test]
-    fn test_all_increasing() {
+    fn test_is_safe_increasing() {
         let report = vec![1, 3, 6, 7, 9];
-        assert!(all_increasing(&report));
+        assert!(is_safe(&report, &rules(Directions::Increasing)));
+        assert!(!is_safe(&report, &rules(Directions::Decreasing)));
 
         let report = vec![1, 3, 6, 9, 7];
-        assert!(!all_increasing(&report));
+        assert!(!is_safe(&report, &rules(Directions::Increasing)));
     }
 
     #[test]
-    fn test_all_respect_max_diff() {
+    fn test_is_safe_steps() {
         let report = vec![1, 3, 6, 7, 9];
-        assert!(all_respect_max_diff(&report, 3));
+        assert!(is_safe(&report, &SafetyRules::default()));
+        let big_steps = SafetyRules {
+            min_step: 2,
+            ..SafetyRules::default()
+        };
+        assert!(!is_safe(&report, &big_steps));
+
+        let report = vec![1, 3, 7, 8, 9];
+        assert!(!is_safe(&report, &SafetyRules::default()));
+        let long_steps = SafetyRules {
+            max_step: 4,
+            ..SafetyRules::default()
+        };
+        assert!(is_safe(&report, &long_steps));
 
-        let report = vec![1, 3, 6, 5, 9];
-        assert!(!all_respect_max_diff(&report, 3));
+        let report = vec![1, 3, 3, 4];
+        assert!(!is_safe(&report, &SafetyRules::default()));
+        let equal_steps = SafetyRules {
+            allow_equal: true,
+            ..SafetyRules::default()
+        };
+        assert!(is_safe(&report, &equal_steps));
+    }
+
+    #[test]
+    fn test_rules_from_options() {
+        assert_eq!(
+            SafetyRules::from_options(&Options::default()),
+            SafetyRules::default()
+        );
+        let options = Options::parse(
+            &[
+                "--max-step=5",
+                "--directions=decreasing",
+                "--allow-equal",
+                "--tolerance=2",
+            ]
+            .map(String::from),
+        );
+        assert_eq!(
+            SafetyRules::from_options(&options),
+            SafetyRules {
+                min_step: 1,
+                max_step: 5,
+                directions: Directions::Decreasing,
+                allow_equal: true,
+                tolerance: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_rules_allow_equal_value() {
+        let options = Options::parse(&["--allow-equal=false".to_string()]);
+        assert!(!SafetyRules::from_options(&options).allow_equal);
+        let options = Options::parse(&["--allow-equal=true".to_string()]);
+        assert!(SafetyRules::from_options(&options).allow_equal);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid steps, need 0 <= --min-step <= --max-step but got 4 and 2")]
+    fn test_rules_min_step_above_max_step() {
+        SafetyRules::from_options(&Options::parse(
+            &["--min-step=4", "--max-step=2"].map(String::from),
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid steps, need 0 <= --min-step <= --max-step but got -1 and 3")]
+    fn test_rules_negative_step() {
+        SafetyRules::from_options(&Options::parse(&["--min-step=-1".to_string()]));
+    }
+
+    #[test]
+    fn test_diagnose() {
+        let reports = parse_input(&get_input(0)).unwrap();
+        let diagnoses = reports
+            .iter()
+            .map(|report| diagnose(report, &SafetyRules::default()))
+            .collect::<Vec<Diagnosis>>();
+        assert_eq!(
+            diagnoses
+                .iter()
+                .map(|diagnosis| diagnosis.verdict.clone())
+                .collect::<Vec<Verdict>>(),
+            vec![
+                Verdict::Safe,
+                Verdict::Unsafe,
+                Verdict::Unsafe,
+                Verdict::SafeWithout(vec![2]),
+                Verdict::SafeWithout(vec![3]),
+                Verdict::Safe
+            ]
+        );
+        assert_eq!(
+            diagnoses[1].violations,
+            vec![Violation::Gap {
+                index: 1,
+                levels: (2, 7),
+                min: 1,
+                max: 3
+            }]
+        );
+        assert_eq!(
+            diagnoses[3].violations,
+            vec![Violation::Direction {
+                index: 1,
+                levels: (3, 2),
+                increasing: true
+            }]
+        );
+        assert_eq!(
+            diagnoses[3].to_string(),
+            "safe without the levels at 2; 3 -> 2 at 1 doesn't increase"
+        );
+        assert_eq!(
+            diagnoses[4].to_string(),
+            "safe without the levels at 3; 4 -> 4 at 2 doesn't decrease"
+        );
+
+        let rules = SafetyRules {
+            min_step: 2,
+            directions: Directions::Increasing,
+            tolerance: 2,
+            ..SafetyRules::default()
+        };
+        let diagnosis = diagnose(&vec![9, 1, 3, 4, 6], &rules);
+        assert_eq!(diagnosis.verdict, Verdict::SafeWithout(vec![0, 3]));
+        assert_eq!(
+            diagnosis.to_string(),
+            "safe without the levels at 0, 3; 9 -> 1 at 0 doesn't increase; \
+             9 -> 1 at 0 doesn't change by 2 to 3; 3 -> 4 at 2 doesn't change by 2 to 3"
+        );
     }
 
     #[test]
@@ -138,4 +572,137 @@ mod test {
     fn test_full_part2() {
         assert_eq!(solve_part2(&get_input(0)), "4");
     }
+
+    #[test]
+    fn test_try_solve() {
+        assert_eq!(
+            try_solve(&String::from("1 2\n3 x")),
+            Err(ParseError::at_line(2, "invalid value \"x\""))
+        );
+        // A single level is safe, huge steps aren't
+        let input = format!("5\n{} {}", Num::MIN, Num::MAX);
+        assert_eq!(try_solve(&input), Ok(("1".to_string(), "2".to_string())));
+    }
+
+    fn tolerating(tolerance: usize) -> SafetyRules {
+        SafetyRules {
+            tolerance,
+            ..SafetyRules::default()
+        }
+    }
+
+    #[test]
+    fn test_removals_to_make_safe() {
+        assert_eq!(
+            removals_to_make_safe(&[1, 2, 3], &tolerating(0)),
+            Some(vec![])
+        );
+        assert_eq!(
+            removals_to_make_safe(&[1, 3, 2, 4, 5], &tolerating(0)),
+            None
+        );
+        assert_eq!(
+            removals_to_make_safe(&[1, 3, 2, 4, 5], &tolerating(1)),
+            Some(vec![2])
+        );
+        assert_eq!(
+            removals_to_make_safe(&[9, 1, 2, 9, 3, 4], &tolerating(1)),
+            None
+        );
+        assert_eq!(
+            removals_to_make_safe(&[9, 1, 2, 9, 3, 4], &tolerating(2)),
+            Some(vec![0, 3])
+        );
+        // Removing levels next to each other
+        assert_eq!(
+            removals_to_make_safe(&[1, 2, 9, 9, 3], &tolerating(2)),
+            Some(vec![2, 3])
+        );
+        assert_eq!(
+            removals_to_make_safe(&[5, 4, 4, 4, 3], &tolerating(2)),
+            Some(vec![2, 3])
+        );
+        assert_eq!(removals_to_make_safe(&[], &tolerating(0)), Some(vec![]));
+    }
+
+    /// Tries every way of removing up to `rules.tolerance` levels
+    fn brute_force_tolerant(report: &Report, rules: &SafetyRules) -> bool {
+        is_safe(report, rules)
+            || (rules.tolerance > 0
+                && (0..report.len()).any(|i| {
+                    brute_force_tolerant(
+                        &[&report[..i], &report[i + 1..]].concat(),
+                        &tolerating_with(rules, rules.tolerance - 1),
+                    )
+                }))
+    }
+
+    fn tolerating_with(rules: &SafetyRules, tolerance: usize) -> SafetyRules {
+        SafetyRules {
+            tolerance,
+            ..rules.clone()
+        }
+    }
+
+    /// Short reports with small steps, so many are nearly safe
+    fn report() -> impl Strategy<Value = Report> {
+        prop::collection::vec(-4..5 as Num, 0..10).prop_map(|steps| {
+            steps
+                .iter()
+                .scan(50, |level, step| {
+                    *level += step;
+                    Some(*level)
+                })
+                .collect()
+        })
+    }
+
+    fn any_rules() -> impl Strategy<Value = SafetyRules> {
+        (
+            0..3 as Num,
+            0..4 as Num,
+            prop::sample::select(vec![
+                Directions::Increasing,
+                Directions::Decreasing,
+                Directions::Either,
+            ]),
+            any::<bool>(),
+            0..4usize,
+        )
+            .prop_map(|(min_step, extra, directions, allow_equal, tolerance)| {
+                SafetyRules {
+                    min_step,
+                    max_step: min_step + extra,
+                    directions,
+                    allow_equal,
+                    tolerance,
+                }
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_removals_match_brute_force(report in report(), rules in any_rules()) {
+            let removals = removals_to_make_safe(&report, &rules);
+            prop_assert_eq!(removals.is_some(), brute_force_tolerant(&report, &rules));
+            if let Some(removals) = removals {
+                let kept = (0..report.len())
+                    .filter(|ix| !removals.contains(ix))
+                    .map(|ix| report[ix])
+                    .collect::<Report>();
+                prop_assert!(is_safe(&kept, &rules));
+                // And no fewer removals would do
+                prop_assert!(
+                    removals.is_empty()
+                        || !brute_force_tolerant(&report, &tolerating_with(&rules, removals.len() - 1))
+                );
+            }
+        }
+
+        #[test]
+        fn prop_diagnose_matches_is_safe(report in report(), rules in any_rules()) {
+            let diagnosis = diagnose(&report, &rules);
+            prop_assert_eq!(diagnosis.violations.is_empty(), is_safe(&report, &rules));
+        }
+    }
 }
@@ -1,84 +1,575 @@
-use crate::aoc::input::read_input_for_day;
-use fancy_regex::Regex;
+use crate::aoc::image::{Color, GREEN, GREY, RED};
+use crate::aoc::input::{read_input_for_day, ParseError};
+use crate::aoc::options::Options;
+use itertools::Itertools;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::ops::Range;
 
 pub fn run() {
+    run_with(&Options::default());
+}
+
+/// `--trace` prints every instruction that runs in part 2 (or `--part=1`),
+/// with its span and the running total. `--annotate` prints the memory with
+/// the accepted, disabled and malformed instructions highlighted, and
+/// `--annotate=<path>` saves that as an HTML page. `--stream=<path>` solves a
+/// memory dump of any size (`-` for stdin) a chunk at a time instead of the input.
+pub fn run_with(options: &Options) {
     println!("Day 3 Solutions");
     println!("---------------");
+    if let Some(path) = options.get("stream") {
+        let (part1, part2) = stream_both_parts(path)
+            .unwrap_or_else(|err| panic!("Couldn't stream the memory from {path}: {err}"));
+        println!("\tPart1: {part1}");
+        println!("\tPart2: {part2}");
+        return;
+    }
     let input = read_input_for_day(3);
     let part1 = solve_part1(&input);
     let part2 = solve_part2(&input);
     println!("\tPart1: {part1}");
     println!("\tPart2: {part2}");
+
+    let toggles = toggles_for_part(options);
+    if options.flag("trace") {
+        println!("{}", trace(&input, INSTRUCTIONS, toggles));
+    }
+    if let Some(path) = options.get("annotate") {
+        let spans = annotate(&input, INSTRUCTIONS, toggles);
+        if path.is_empty() {
+            println!("{}", render_ansi(&input, &spans));
+        } else {
+            fs::write(path, render_html(&input, &spans)).unwrap_or_else(|err| {
+                panic!("Couldn't save the annotated memory to {path}: {err}")
+            });
+            println!("\tSaved annotated memory to {path}");
+        }
+    }
+}
+
+/// Whether `do()` and `don't()` count in the `--part` to show, 2 by default
+fn toggles_for_part(options: &Options) -> bool {
+    options.part(2) == 2
 }
 
-type Num = i32;
+/// Both answers for the memory at `path`, or stdin for `-`, in a single pass
+fn stream_both_parts(path: &str) -> io::Result<(Num, Num)> {
+    let reader: Box<dyn Read> = if path == "-" {
+        Box::new(io::stdin().lock())
+    } else {
+        Box::new(File::open(path)?)
+    };
+    let mut part1 = Interpreter::new(false);
+    let mut part2 = Interpreter::new(true);
+    stream_instructions(reader, INSTRUCTIONS, CHUNK_SIZE, |instruction| {
+        part1.step(instruction);
+        part2.step(instruction);
+    })?;
+    Ok((part1.total(), part2.total()))
+}
+
+type Num = i64;
 type Pair = (Num, Num);
 type Pairs = Vec<Pair>;
+/// Byte range of a token in the memory
+pub type Span = Range<usize>;
+/// Bytes read at a time when streaming
+pub const CHUNK_SIZE: usize = 64 * 1024;
+/// What an instruction does when it runs, and whether it had any effect
+pub type Semantics = fn(&mut Interpreter, &[Num]) -> bool;
+
+/// How to recognize an instruction, `name(arg,...)`, and what it does
+#[derive(Debug, Clone, Copy)]
+pub struct InstructionDef {
+    pub name: &'static str,
+    pub arity: usize,
+    /// Fewest and most digits each argument may have
+    pub digits: (usize, usize),
+    pub semantics: Semantics,
+}
+
+/// The instructions of the puzzle: `mul(x,y)`, `do()` and `don't()`
+pub const INSTRUCTIONS: &[InstructionDef] = &[
+    InstructionDef {
+        name: "mul",
+        arity: 2,
+        digits: (1, 3),
+        semantics: |interpreter, args| {
+            args[0]
+                .checked_mul(args[1])
+                .is_some_and(|product| interpreter.add(product))
+        },
+    },
+    InstructionDef {
+        name: "do",
+        arity: 0,
+        digits: (0, 0),
+        semantics: |interpreter, _| interpreter.toggle(State::Enabled),
+    },
+    InstructionDef {
+        name: "don't",
+        arity: 0,
+        digits: (0, 0),
+        semantics: |interpreter, _| interpreter.toggle(State::Disabled),
+    },
+];
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Instruction {
+    pub name: &'static str,
+    pub args: Vec<Num>,
+}
+
+impl Instruction {
+    pub fn new(name: &'static str, args: &[Num]) -> Self {
+        Self {
+            name,
+            args: args.to_vec(),
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}({})", self.name, self.args.iter().join(","))
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Token {
+    pub instruction: Instruction,
+    pub span: Span,
+}
+
+/// A number with `digits` digits at the start of `bytes`, and how many there
+/// are. Too many digits for a `Num` don't make a number either.
+fn scan_number(bytes: &[u8], (min_digits, max_digits): (usize, usize)) -> Option<(Num, usize)> {
+    let n_digits = bytes
+        .iter()
+        .take(max_digits + 1)
+        .take_while(|b| b.is_ascii_digit())
+        .count();
+    if !(min_digits.max(1)..=max_digits).contains(&n_digits) {
+        return None;
+    }
+    let num = bytes[..n_digits].iter().try_fold(0 as Num, |num, digit| {
+        num.checked_mul(10)?.checked_add(Num::from(digit - b'0'))
+    })?;
+    Some((num, n_digits))
+}
+
+/// The `def` instruction at the start of `bytes` and its length, or how many
+/// bytes matched before it broke off. That's 0 unless it got past `name(`,
+/// so words that merely contain a name, like the `do` in `undo`, aren't broken
+/// instructions.
+fn scan_instruction(bytes: &[u8], def: &InstructionDef) -> Result<(Instruction, usize), usize> {
+    let literal = |i: usize, literal: &[u8]| {
+        bytes[i..]
+            .starts_with(literal)
+            .then_some(i + literal.len())
+            .ok_or(i)
+    };
+    let mut i = literal(0, def.name.as_bytes()).map_err(|_| 0usize)?;
+    i = literal(i, b"(").map_err(|_| 0usize)?;
+    let mut args = Vec::with_capacity(def.arity);
+    for n in 0..def.arity {
+        if n > 0 {
+            i = literal(i, b",")?;
+        }
+        let (arg, n_digits) = scan_number(&bytes[i..], def.digits).ok_or(i)?;
+        args.push(arg);
+        i += n_digits;
+    }
+    i = literal(i, b")")?;
+    let instruction = Instruction {
+        name: def.name,
+        args,
+    };
+    Ok((instruction, i))
+}
+
+/// The first of `defs` starting at the start of `bytes` and its length, or
+/// how far the longest broken one got
+fn scan_at(bytes: &[u8], defs: &[InstructionDef]) -> Result<(Instruction, usize), usize> {
+    let mut matched = 0;
+    for def in defs {
+        match scan_instruction(bytes, def) {
+            Ok(found) => return Ok(found),
+            Err(len) => matched = matched.max(len),
+        }
+    }
+    Err(matched)
+}
+
+/// Every well formed instruction of `defs` in `bytes`, in order. Anything
+/// else is skipped a byte at a time, so an instruction can start inside a
+/// broken one. Broken instructions that got past `name(` go to `on_malformed`.
+fn scan(bytes: &[u8], defs: &[InstructionDef], mut on_malformed: impl FnMut(Span)) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match scan_at(&bytes[i..], defs) {
+            Ok((instruction, len)) => {
+                tokens.push(Token {
+                    instruction,
+                    span: i..i + len,
+                });
+                i += len;
+            }
+            Err(matched) => {
+                if matched > 0 {
+                    on_malformed(i..i + matched);
+                }
+                i += 1;
+            }
+        }
+    }
+
+    tokens
+}
+
+pub fn tokenize_with(memory: &str, defs: &[InstructionDef]) -> Vec<Token> {
+    scan(memory.as_bytes(), defs, |_| {})
+}
+
+pub fn tokenize(memory: &str) -> Vec<Token> {
+    tokenize_with(memory, INSTRUCTIONS)
+}
 
-fn parse_input(input: &String) -> Pairs {
-    let valid_memory_pattern = Regex::new(r"(?<=mul\()([0-9]{1,3}),([0-9]{1,3})(?=\))").unwrap();
-    let mut pairs = Pairs::new();
-    for capture in valid_memory_pattern.captures_iter(input) {
-        let group = capture.unwrap();
-        let x = group.get(1).unwrap().as_str();
-        let y = group.get(2).unwrap().as_str();
-        pairs.push((x.parse::<Num>().unwrap(), y.parse::<Num>().unwrap()));
-    }
-    pairs
-    /*
-    valid_memory_pattern
-        .captures_iter(input)
-        .map(|cap| cap.unwrap())
-        .map(|group| {
-            (
-                group.get(1).unwrap().as_str(),
-                group.get(2).unwrap().as_str(),
-            )
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum State {
+    Enabled,
+    Disabled,
+}
+
+/// Runs instructions one at a time with the semantics from `defs`. Toggles
+/// switch the adding instructions on and off, unless the interpreter ignores
+/// them.
+#[derive(Debug, Clone)]
+pub struct Interpreter<'a> {
+    defs: &'a [InstructionDef],
+    state: State,
+    toggles: bool,
+    total: Num,
+}
+
+impl<'a> Interpreter<'a> {
+    pub fn new(toggles: bool) -> Self {
+        Self::with_defs(INSTRUCTIONS, toggles)
+    }
+
+    pub fn with_defs(defs: &'a [InstructionDef], toggles: bool) -> Self {
+        Self {
+            defs,
+            state: State::Enabled,
+            toggles,
+            total: 0,
+        }
+    }
+
+    /// Runs `instruction`, and whether it had any effect. Instructions that
+    /// aren't in the interpreter's defs, or with the wrong number of
+    /// arguments, have none.
+    pub fn step(&mut self, instruction: &Instruction) -> bool {
+        let Some(def) = self
+            .defs
+            .iter()
+            .find(|def| def.name == instruction.name && def.arity == instruction.args.len())
+        else {
+            return false;
+        };
+        (def.semantics)(self, &instruction.args)
+    }
+
+    /// Adds `num` to the total if enabled and the total doesn't overflow
+    pub fn add(&mut self, num: Num) -> bool {
+        if self.state == State::Disabled {
+            return false;
+        }
+        let Some(total) = self.total.checked_add(num) else {
+            return false;
+        };
+        self.total = total;
+        true
+    }
+
+    /// Switches to `state` unless toggles are ignored
+    pub fn toggle(&mut self, state: State) -> bool {
+        if !self.toggles {
+            return false;
+        }
+        self.state = state;
+        true
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    pub fn total(&self) -> Num {
+        self.total
+    }
+}
+
+/// Sum of the `mul`s in `tokens`, switched on and off by `do()` and
+/// `don't()` if `toggles`
+pub fn evaluate(tokens: &[Token], toggles: bool) -> Num {
+    let mut interpreter = Interpreter::new(toggles);
+    for token in tokens {
+        interpreter.step(&token.instruction);
+    }
+    interpreter.total()
+}
+
+/// Bytes to read at a position to tell whether one of `defs` starts there: the
+/// longest instruction, and a digit more to see that an argument is too long
+fn lookahead(defs: &[InstructionDef]) -> usize {
+    defs.iter()
+        .map(|def| def.name.len() + 2 + def.arity * (def.digits.1 + 1))
+        .max()
+        .unwrap_or(1)
+}
+
+/// Feeds every well formed instruction of `defs` in `reader` to
+/// `on_instruction`, like `tokenize_with` but reading `chunk_size` bytes at a
+/// time. Only the tail of a chunk that could start an instruction is kept for
+/// the next one, so memory stays constant however long the dump is.
+pub fn stream_instructions(
+    mut reader: impl Read,
+    defs: &[InstructionDef],
+    chunk_size: usize,
+    mut on_instruction: impl FnMut(&Instruction),
+) -> io::Result<()> {
+    let lookahead = lookahead(defs);
+    let mut buffer = Vec::with_capacity(lookahead + chunk_size);
+    loop {
+        let len = buffer.len();
+        buffer.resize(len + chunk_size.max(1), 0);
+        let read = loop {
+            match reader.read(&mut buffer[len..]) {
+                Ok(read) => break read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
+        };
+        buffer.truncate(len + read);
+        let done = read == 0;
+
+        let mut i = 0;
+        while i < buffer.len() && (done || buffer.len() - i >= lookahead) {
+            match scan_at(&buffer[i..], defs) {
+                Ok((instruction, len)) => {
+                    on_instruction(&instruction);
+                    i += len;
+                }
+                Err(_) => i += 1,
+            }
+        }
+        buffer.drain(..i);
+        if done {
+            return Ok(());
+        }
+    }
+}
+
+/// Sum of the `mul`s in `reader` like `evaluate`, without loading it all
+pub fn evaluate_stream(reader: impl Read, toggles: bool) -> io::Result<Num> {
+    let mut interpreter = Interpreter::new(toggles);
+    stream_instructions(reader, INSTRUCTIONS, CHUNK_SIZE, |instruction| {
+        interpreter.step(instruction);
+    })?;
+    Ok(interpreter.total())
+}
+
+/// A line per instruction that had an effect: its span, the instruction and
+/// the running total
+pub fn trace(memory: &str, defs: &[InstructionDef], toggles: bool) -> String {
+    let mut interpreter = Interpreter::with_defs(defs, toggles);
+    tokenize_with(memory, defs)
+        .into_iter()
+        .filter_map(|token| {
+            interpreter.step(&token.instruction).then(|| {
+                format!(
+                    "{}..{}\t{}\t{}",
+                    token.span.start,
+                    token.span.end,
+                    token.instruction,
+                    interpreter.total()
+                )
+            })
         })
-        .map(|(x, y)| {
-            (
-                x.parse::<Num>().unwrap(),
-                y.parse::<Num>().unwrap(),
-            )
+        .join("\n")
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Category {
+    /// Ran and had an effect
+    Accepted,
+    /// Well formed, but switched off by `don't()` (or a toggle in part 1)
+    Disabled,
+    /// Starts like an instruction, but isn't one
+    Malformed,
+}
+
+impl Category {
+    fn color(&self) -> Color {
+        match self {
+            Category::Accepted => GREEN,
+            Category::Disabled => GREY,
+            Category::Malformed => RED,
+        }
+    }
+
+    fn class(&self) -> &'static str {
+        match self {
+            Category::Accepted => "accepted",
+            Category::Disabled => "disabled",
+            Category::Malformed => "malformed",
+        }
+    }
+}
+
+/// The spans of every instruction and malformed instruction in `memory`, in
+/// order. An instruction can start inside a malformed span, so they can overlap.
+pub fn annotate(memory: &str, defs: &[InstructionDef], toggles: bool) -> Vec<(Span, Category)> {
+    let mut spans = Vec::new();
+    let tokens = scan(memory.as_bytes(), defs, |span| {
+        spans.push((span, Category::Malformed))
+    });
+    let mut interpreter = Interpreter::with_defs(defs, toggles);
+    for token in tokens {
+        let category = if interpreter.step(&token.instruction) {
+            Category::Accepted
+        } else {
+            Category::Disabled
+        };
+        spans.push((token.span, category));
+    }
+    spans.sort_by_key(|(span, _)| span.start);
+
+    spans
+}
+
+/// `memory` in pieces, each with the category of its span if it has one. Where
+/// spans overlap the later one wins, and an earlier span carries on after it.
+fn pieces<'a>(memory: &'a str, spans: &[(Span, Category)]) -> Vec<(&'a str, Option<Category>)> {
+    // Which span each byte shows
+    let mut owners = vec![None; memory.len()];
+    for (ix, (span, _)) in spans.iter().enumerate() {
+        owners[span.clone()].fill(Some(ix));
+    }
+
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    for end in 1..=owners.len() {
+        if end == owners.len() || owners[end] != owners[start] {
+            let category = owners[start].map(|ix| spans[ix].1);
+            pieces.push((&memory[start..end], category));
+            start = end;
+        }
+    }
+
+    pieces
+}
+
+/// `memory` with accepted instructions in green, disabled ones in grey and
+/// malformed ones in red, in 24-bit ANSI colours
+pub fn render_ansi(memory: &str, spans: &[(Span, Category)]) -> String {
+    pieces(memory, spans)
+        .into_iter()
+        .map(|(text, category)| match category {
+            Some(category) => {
+                let [r, g, b] = category.color();
+                format!("\x1b[1;38;2;{r};{g};{b}m{text}\x1b[0m")
+            }
+            None => text.to_string(),
         })
-        .collect::<Pairs>()
-    */
+        .collect()
 }
 
-fn parse_input_2(input: &String) -> Pairs {
-    let valid_memory_pattern =
-        Regex::new(r"(don't|do)|(?<=mul\()([0-9]{1,3},[0-9]{1,3})(?=\))").unwrap();
-    let mut pairs = Pairs::new();
-    let mut do_ = true;
-    for capture in valid_memory_pattern.captures_iter(input) {
-        let group = capture.unwrap();
-        let subcap = group.get(0).unwrap().as_str();
-        if subcap == "do" || subcap == "don't" {
-            do_ = subcap == "do";
-        } else if do_ {
-            let nums = subcap
-                .split(',')
-                .map(|n| n.parse::<Num>().unwrap())
-                .collect::<Vec<Num>>();
-            pairs.push((nums[0], nums[1]));
+/// A standalone HTML page of `memory` with the spans highlighted like
+/// `render_ansi`, and a legend
+pub fn render_html(memory: &str, spans: &[(Span, Category)]) -> String {
+    let escape = |text: &str| {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    };
+    let categories = [Category::Accepted, Category::Disabled, Category::Malformed];
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Day 3 memory</title>\n<style>\n\
+         pre { white-space: pre-wrap; word-break: break-all; }\n",
+    );
+    for category in categories {
+        let [r, g, b] = category.color();
+        html.push_str(&format!(
+            ".{} {{ color: rgb({r},{g},{b}); font-weight: bold; }}\n",
+            category.class()
+        ));
+    }
+    html.push_str("</style>\n</head>\n<body>\n<p>");
+    html.push_str(
+        &categories
+            .iter()
+            .map(|category| format!("<span class=\"{0}\">{0}</span>", category.class()))
+            .join(" "),
+    );
+    html.push_str("</p>\n<pre>");
+    for (text, category) in pieces(memory, spans) {
+        match category {
+            Some(category) => html.push_str(&format!(
+                "<span class=\"{}\">{}</span>",
+                category.class(),
+                escape(text)
+            )),
+            None => html.push_str(&escape(text)),
         }
     }
-    pairs
+    html.push_str("</pre>\n</body>\n</html>\n");
+
+    html
+}
+
+/// The `mul`s that `toggles` leaves on, as pairs
+fn executed_pairs(input: &str, toggles: bool) -> Pairs {
+    let mut interpreter = Interpreter::new(toggles);
+    tokenize(input)
+        .into_iter()
+        .filter(|token| interpreter.step(&token.instruction))
+        .filter(|token| token.instruction.name == "mul")
+        .map(|token| (token.instruction.args[0], token.instruction.args[1]))
+        .collect()
+}
+
+pub fn parse_input(input: &String) -> Pairs {
+    executed_pairs(input, false)
 }
 
-fn solve_part1(input: &String) -> String {
-    let pairs = parse_input(&input);
-    pairs.iter().fold(0, |acc, (x, y)| acc + x * y).to_string()
+pub fn parse_input_2(input: &String) -> Pairs {
+    executed_pairs(input, true)
 }
 
-fn solve_part2(input: &String) -> String {
-    let pairs = parse_input_2(&input);
-    pairs.iter().fold(0, |acc, (x, y)| acc + x * y).to_string()
+pub fn solve_part1(input: &String) -> String {
+    evaluate(&tokenize(input), false).to_string()
+}
+
+pub fn solve_part2(input: &String) -> String {
+    evaluate(&tokenize(input), true).to_string()
+}
+
+/// Both answers. Any text is valid corrupted memory, so this never fails.
+pub fn try_solve(input: &String) -> Result<(String, String), ParseError> {
+    Ok((solve_part1(input), solve_part2(input)))
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use fancy_regex::Regex;
+    use proptest::prelude::*;
 
     const INPUT: [&str; 2] = [
         "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))",
@@ -102,6 +593,317 @@ mod test {
         assert_eq!(pairs[1], (8, 5));
     }
 
+    #[test]
+    fn test_tokenize() {
+        let tokens = tokenize(&get_input(1));
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    instruction: Instruction::new("mul", &[2, 4]),
+                    span: 1..9
+                },
+                Token {
+                    instruction: Instruction::new("don't", &[]),
+                    span: 20..27
+                },
+                Token {
+                    instruction: Instruction::new("mul", &[5, 5]),
+                    span: 28..36
+                },
+                Token {
+                    instruction: Instruction::new("mul", &[11, 8]),
+                    span: 48..57
+                },
+                Token {
+                    instruction: Instruction::new("do", &[]),
+                    span: 59..63
+                },
+                Token {
+                    instruction: Instruction::new("mul", &[8, 5]),
+                    span: 64..72
+                },
+            ]
+        );
+        assert_eq!(&get_input(1)[tokens[1].span.clone()], "don't()");
+        // Instructions can start inside broken ones
+        let tokens = tokenize("mul(1,mul(2,3)do(do())");
+        assert_eq!(tokens[0].span, 6..14);
+        assert_eq!(tokens[1].instruction, Instruction::new("do", &[]));
+        assert_eq!(tokens.len(), 2);
+    }
+
+    #[test]
+    fn test_toggles_need_parentheses() {
+        // A bare "do" or "don't" doesn't switch anything
+        let input = String::from("don'tmul(2,3)don't()mul(1,1)undomul(4,4)do()mul(5,1)");
+        assert_eq!(solve_part1(&input), "28");
+        assert_eq!(solve_part2(&input), "11");
+    }
+
+    #[test]
+    fn test_interpreter() {
+        let mut interpreter = Interpreter::new(true);
+        assert!(interpreter.step(&Instruction::new("mul", &[2, 3])));
+        assert!(interpreter.step(&Instruction::new("don't", &[])));
+        assert_eq!(interpreter.state(), State::Disabled);
+        assert!(!interpreter.step(&Instruction::new("mul", &[2, 3])));
+        assert!(interpreter.step(&Instruction::new("do", &[])));
+        assert_eq!(interpreter.total(), 6);
+
+        // Unknown instructions and wrong arities do nothing
+        assert!(!interpreter.step(&Instruction::new("add", &[1, 2])));
+        assert!(!interpreter.step(&Instruction::new("mul", &[2])));
+        assert_eq!(interpreter.total(), 6);
+
+        // Without toggles do() and don't() do nothing
+        let mut interpreter = Interpreter::new(false);
+        assert!(!interpreter.step(&Instruction::new("don't", &[])));
+        assert!(interpreter.step(&Instruction::new("mul", &[2, 3])));
+    }
+
+    /// The puzzle's instructions plus `add`, `sub` and `doif(x)`, which only
+    /// switches `mul`s back on for a non-zero `x`
+    const EXTENDED: &[InstructionDef] = &[
+        INSTRUCTIONS[0],
+        INSTRUCTIONS[1],
+        INSTRUCTIONS[2],
+        InstructionDef {
+            name: "add",
+            arity: 2,
+            digits: (1, 3),
+            semantics: |interpreter, args| interpreter.add(args[0] + args[1]),
+        },
+        InstructionDef {
+            name: "sub",
+            arity: 2,
+            digits: (1, 3),
+            semantics: |interpreter, args| interpreter.add(args[0] - args[1]),
+        },
+        InstructionDef {
+            name: "doif",
+            arity: 1,
+            digits: (1, 1),
+            semantics: |interpreter, args| args[0] != 0 && interpreter.toggle(State::Enabled),
+        },
+    ];
+
+    #[test]
+    fn test_registered_instructions() {
+        let memory = "add(1,2)sub(3,10)add(1)don't()doif(0)add(5,5)doif(12)doif(1)mul(2,2)";
+        let tokens = tokenize_with(memory, EXTENDED);
+        assert_eq!(
+            tokens
+                .iter()
+                .map(|token| token.instruction.to_string())
+                .join(" "),
+            "add(1,2) sub(3,10) don't() doif(0) add(5,5) doif(1) mul(2,2)"
+        );
+        let mut interpreter = Interpreter::with_defs(EXTENDED, true);
+        for token in &tokens {
+            interpreter.step(&token.instruction);
+        }
+        assert_eq!(interpreter.total(), 3 - 7 + 4);
+        // The puzzle's tokenizer only sees its own instructions
+        assert_eq!(tokenize(memory).len(), 2);
+    }
+
+    #[test]
+    fn test_overflow() {
+        const WIDE: &[InstructionDef] = &[InstructionDef {
+            name: "mul",
+            arity: 2,
+            digits: (1, 30),
+            semantics: INSTRUCTIONS[0].semantics,
+        }];
+        // Too long for a Num, so not an instruction
+        let memory = format!("mul({},1)mul(2,3)", "9".repeat(20));
+        let tokens = tokenize_with(&memory, WIDE);
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].instruction, Instruction::new("mul", &[2, 3]));
+
+        // Products and totals that would overflow have no effect
+        let mut interpreter = Interpreter::with_defs(WIDE, false);
+        let big = Num::MAX / 2 + 1;
+        assert!(!interpreter.step(&Instruction::new("mul", &[big, 2])));
+        assert!(interpreter.step(&Instruction::new("mul", &[big, 1])));
+        assert!(!interpreter.step(&Instruction::new("mul", &[big, 1])));
+        assert_eq!(interpreter.total(), big);
+    }
+
+    #[test]
+    fn test_trace() {
+        assert_eq!(
+            trace(&get_input(1), INSTRUCTIONS, true),
+            "1..9\tmul(2,4)\t8\n\
+             20..27\tdon't()\t8\n\
+             59..63\tdo()\t8\n\
+             64..72\tmul(8,5)\t48"
+        );
+        assert_eq!(
+            trace("mul(2,3)don't()mul(1,1)", INSTRUCTIONS, false),
+            "0..8\tmul(2,3)\t6\n15..23\tmul(1,1)\t7"
+        );
+    }
+
+    #[test]
+    fn test_annotate() {
+        let memory = "mul(1,2)mul[3,7]don't()mul(4,5)mul(1234,5)do()undomul(6,";
+        let spans = annotate(memory, INSTRUCTIONS, true);
+        let annotated = spans
+            .iter()
+            .map(|(span, category)| format!("{}:{category:?}", &memory[span.clone()]))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            annotated,
+            vec![
+                "mul(1,2):Accepted",
+                "don't():Accepted",
+                "mul(4,5):Disabled",
+                "mul(:Malformed",
+                "do():Accepted",
+                "mul(6,:Malformed",
+            ]
+        );
+
+        // An instruction can start right where a malformed one breaks off
+        let memory = "mul(mul(2,3)";
+        let spans = annotate(memory, INSTRUCTIONS, false);
+        assert_eq!(
+            spans,
+            vec![(0..4, Category::Malformed), (4..12, Category::Accepted)]
+        );
+        assert_eq!(
+            pieces(memory, &spans),
+            vec![
+                ("mul(", Some(Category::Malformed)),
+                ("mul(2,3)", Some(Category::Accepted))
+            ]
+        );
+
+        // With an instruction named 1() the malformed mul(1 overlaps it, and
+        // the instruction, coming later, wins
+        const ONE: &[InstructionDef] = &[
+            INSTRUCTIONS[0],
+            InstructionDef {
+                name: "1",
+                arity: 0,
+                digits: (0, 0),
+                semantics: |_, _| true,
+            },
+        ];
+        let memory = "mul(1()";
+        let spans = annotate(memory, ONE, true);
+        assert_eq!(
+            spans,
+            vec![(0..5, Category::Malformed), (4..7, Category::Accepted)]
+        );
+        assert_eq!(
+            pieces(memory, &spans),
+            vec![
+                ("mul(", Some(Category::Malformed)),
+                ("1()", Some(Category::Accepted))
+            ]
+        );
+        // An earlier span carries on after a later one inside it
+        let spans = [(0..7, Category::Malformed), (2..4, Category::Accepted)];
+        assert_eq!(
+            pieces(memory, &spans),
+            vec![
+                ("mu", Some(Category::Malformed)),
+                ("l(", Some(Category::Accepted)),
+                ("1()", Some(Category::Malformed))
+            ]
+        );
+        assert_eq!(pieces("", &[]), vec![]);
+    }
+
+    #[test]
+    fn test_render_annotations() {
+        let memory = "x<mul(2,4)don't()mul(1]";
+        let spans = annotate(memory, INSTRUCTIONS, true);
+        assert_eq!(
+            render_ansi(memory, &spans),
+            "x<\x1b[1;38;2;30;160;60mmul(2,4)\x1b[0m\
+             \x1b[1;38;2;30;160;60mdon't()\x1b[0m\
+             \x1b[1;38;2;220;50;47mmul(1\x1b[0m]"
+        );
+        let html = render_html(memory, &spans);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains(
+            "<pre>x&lt;<span class=\"accepted\">mul(2,4)</span>\
+             <span class=\"accepted\">don't()</span>\
+             <span class=\"malformed\">mul(1</span>]</pre>"
+        ));
+        assert!(html.contains(".malformed { color: rgb(220,50,47);"));
+    }
+
+    /// Reads at most `max` bytes at a time, to split tokens at odd places
+    struct Trickle<'a> {
+        bytes: &'a [u8],
+        max: usize,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.max.min(buf.len()).min(self.bytes.len());
+            buf[..n].copy_from_slice(&self.bytes[..n]);
+            self.bytes = &self.bytes[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_stream_instructions() {
+        let memory = get_input(1);
+        for chunk_size in 1..=memory.len() + 1 {
+            for max in [1, 2, 3, 7] {
+                let mut instructions = Vec::new();
+                let reader = Trickle {
+                    bytes: memory.as_bytes(),
+                    max,
+                };
+                stream_instructions(reader, INSTRUCTIONS, chunk_size, |instruction| {
+                    instructions.push(instruction.clone())
+                })
+                .unwrap();
+                let tokens = tokenize(&memory);
+                assert_eq!(
+                    instructions,
+                    tokens
+                        .into_iter()
+                        .map(|t| t.instruction)
+                        .collect::<Vec<_>>(),
+                    "chunk size {chunk_size}, reads of {max}"
+                );
+            }
+        }
+        // Too many digits is only visible with a digit past the longest mul
+        let mut instructions = Vec::new();
+        stream_instructions("mul(1,1234)".as_bytes(), INSTRUCTIONS, 1, |instruction| {
+            instructions.push(instruction.clone())
+        })
+        .unwrap();
+        assert!(instructions.is_empty());
+        assert_eq!(evaluate_stream(get_input(1).as_bytes(), true).unwrap(), 48);
+        assert_eq!(evaluate_stream(&b""[..], true).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_toggles_for_part() {
+        assert!(toggles_for_part(&Options::default()));
+        assert!(!toggles_for_part(&Options::parse(
+            &["--part=1".to_string()]
+        )));
+        assert!(toggles_for_part(&Options::parse(&["--part=2".to_string()])));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid value for --part: 3, expected 1 or 2")]
+    fn test_toggles_for_invalid_part() {
+        toggles_for_part(&Options::parse(&["--part=3".to_string()]));
+    }
+
     #[test]
     fn test_full_part1() {
         assert_eq!(solve_part1(&get_input(0)), "161");
@@ -111,4 +913,126 @@ mod test {
     fn test_full_part2() {
         assert_eq!(solve_part2(&get_input(1)), "48");
     }
+
+    /// The same pattern as `parse_input`, collected with iterators
+    fn parse_input_iter(input: &str) -> Pairs {
+        let valid_memory_pattern =
+            Regex::new(r"(?<=mul\()([0-9]{1,3}),([0-9]{1,3})(?=\))").unwrap();
+        valid_memory_pattern
+            .captures_iter(input)
+            .map(|cap| cap.unwrap())
+            .map(|group| {
+                (
+                    group.get(1).unwrap().as_str(),
+                    group.get(2).unwrap().as_str(),
+                )
+            })
+            .map(|(x, y)| (x.parse::<Num>().unwrap(), y.parse::<Num>().unwrap()))
+            .collect::<Pairs>()
+    }
+
+    /// 1 to 3 digits at the start of `bytes`, kept apart from the tokenizer's
+    /// `scan_number` so the reference doesn't share its bugs
+    fn reference_number(bytes: &[u8]) -> Option<(Num, usize)> {
+        let n_digits = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+        if (1..=3).contains(&n_digits) {
+            let num = std::str::from_utf8(&bytes[..n_digits]).unwrap();
+            Some((num.parse().unwrap(), n_digits))
+        } else {
+            None
+        }
+    }
+
+    /// Byte by byte scan without regexes or the tokenizer, toggling on `do()`
+    /// and `don't()` if `toggles`
+    fn reference_sum(input: &str, toggles: bool) -> Num {
+        let bytes = input.as_bytes();
+        let mut sum = 0;
+        let mut do_ = true;
+        let mut i = 0;
+        while i < bytes.len() {
+            let rest = &bytes[i..];
+            if toggles && rest.starts_with(b"don't()") {
+                do_ = false;
+                i += 7;
+            } else if toggles && rest.starts_with(b"do()") {
+                do_ = true;
+                i += 4;
+            } else if rest.starts_with(b"mul(") {
+                i += 4;
+                let Some((x, n_x)) = reference_number(&bytes[i..]) else {
+                    continue;
+                };
+                if bytes.get(i + n_x) != Some(&b',') {
+                    continue;
+                }
+                let Some((y, n_y)) = reference_number(&bytes[i + n_x + 1..]) else {
+                    continue;
+                };
+                if bytes.get(i + n_x + 1 + n_y) == Some(&b')') {
+                    if do_ {
+                        sum += x * y;
+                    }
+                    i += n_x + 1 + n_y;
+                }
+            } else {
+                i += 1;
+            }
+        }
+
+        sum
+    }
+
+    /// Corrupted memory made of instruction fragments and noise
+    fn memory() -> impl Strategy<Value = String> {
+        let fragment = prop_oneof![
+            Just("mul(".to_string()),
+            Just("mul[".to_string()),
+            Just("do()".to_string()),
+            Just("don't()".to_string()),
+            Just("undo".to_string()),
+            Just("do".to_string()),
+            Just("don't".to_string()),
+            Just(",".to_string()),
+            Just(")".to_string()),
+            "[0-9]{1,4}",
+            "[a-z!@%^&*( ]{1,3}",
+        ];
+        prop::collection::vec(fragment, 0..40).prop_map(|fragments| fragments.concat())
+    }
+
+    proptest! {
+        #[test]
+        fn prop_parse_input_iter(input in memory()) {
+            prop_assert_eq!(parse_input(&input), parse_input_iter(&input));
+        }
+
+        #[test]
+        fn prop_part1_matches_reference(input in memory()) {
+            prop_assert_eq!(solve_part1(&input), reference_sum(&input, false).to_string());
+        }
+
+        #[test]
+        fn prop_stream_matches_evaluate(
+            memory in memory(),
+            chunk_size in 1..40usize,
+            max in 1..40usize,
+        ) {
+            let tokens = tokenize(&memory);
+            for toggles in [false, true] {
+                let mut interpreter = Interpreter::new(toggles);
+                let reader = Trickle { bytes: memory.as_bytes(), max };
+                stream_instructions(reader, INSTRUCTIONS, chunk_size, |instruction| {
+                    interpreter.step(instruction);
+                })
+                .unwrap();
+                prop_assert_eq!(interpreter.total(), evaluate(&tokens, toggles));
+            }
+        }
+
+        #[test]
+        fn prop_part2_matches_reference(input in memory()) {
+            prop_assert_eq!(solve_part2(&input), reference_sum(&input, true).to_string());
+        }
+    }
 }
@@ -1,4 +1,4 @@
-use crate::aoc::input::read_input_for_day;
+use crate::aoc::input::{parse_grid, read_input_for_day, ParseError};
 
 pub fn run() {
     println!("Day 4 Solutions");
@@ -13,12 +13,8 @@ pub fn run() {
 type Row = Vec<char>;
 type Grid = Vec<Row>;
 
-fn parse_input(input: &String) -> Grid {
-    input
-        .trim()
-        .split('\n')
-        .map(|line| line.chars().collect::<Row>())
-        .collect::<Grid>()
+pub fn parse_input(input: &String) -> Result<Grid, ParseError> {
+    parse_grid(input.trim())
 }
 
 type Indexes = Vec<(usize, usize)>;
@@ -132,17 +128,8 @@ fn count_n_x_mas_at((i, j): (usize, usize), grid: &Grid) -> u32 {
     0
 }
 
-fn solve_part1(input: &String) -> String {
-    let grid = parse_input(input);
-    /*
-    let mut n_xmas = 0;
-    for i in 0..grid.len() {
-        for j in 0..grid[0].len() {
-            n_xmas += count_n_xmas_at((i, j), &grid);
-        }
-    }
-    n_xmas.to_string()
-    */
+pub fn solve_part1(input: &String) -> String {
+    let grid = parse_input(input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
     (0..grid.len())
         .map(|i| {
             (0..grid[0].len())
@@ -153,8 +140,8 @@ fn solve_part1(input: &String) -> String {
         .to_string()
 }
 
-fn solve_part2(input: &String) -> String {
-    let grid = parse_input(input);
+pub fn solve_part2(input: &String) -> String {
+    let grid = parse_input(input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
     (1..grid.len())
         .map(|i| {
             (1..grid[0].len())
@@ -165,9 +152,16 @@ fn solve_part2(input: &String) -> String {
         .to_string()
 }
 
+/// Both answers, or why `input` can't be solved
+pub fn try_solve(input: &String) -> Result<(String, String), ParseError> {
+    parse_input(input)?;
+    Ok((solve_part1(input), solve_part2(input)))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use proptest::prelude::*;
 
     const INPUT: [&str; 5] = [
         "
@@ -231,19 +225,19 @@ M.M.M.M.M.
 
     #[test]
     fn test_parse_input() {
-        let grid = parse_input(&get_input(0));
+        let grid = parse_input(&get_input(0)).unwrap();
         assert_eq!(grid[0][0], '.');
     }
 
     #[test]
     fn test_count_n_xmas_at() {
-        let grid = parse_input(&get_input(1));
+        let grid = parse_input(&get_input(1)).unwrap();
         assert_eq!(count_n_xmas_at((9, 9), &grid), 2);
     }
 
     #[test]
     fn test_indexes() {
-        let grid = parse_input(&get_input(0));
+        let grid = parse_input(&get_input(0)).unwrap();
         assert_eq!(
             indexes_right((0, 0), 4, &grid),
             vec![(0, 0), (0, 1), (0, 2), (0, 3)]
@@ -293,4 +287,95 @@ M.M.M.M.M.
     fn test_full_part2() {
         assert_eq!(solve_part2(&get_input(4)), "9");
     }
+
+    #[test]
+    fn test_try_solve() {
+        assert_eq!(
+            try_solve(&String::from("XMAS\nXM")),
+            Err(ParseError::at_line(2, "expected 4 cells, found 2"))
+        );
+        assert_eq!(
+            try_solve(&String::from("X")),
+            Ok(("0".to_string(), "0".to_string()))
+        );
+    }
+
+    fn char_at(grid: &Grid, i: isize, j: isize) -> Option<char> {
+        if i < 0 || j < 0 {
+            return None;
+        }
+        grid.get(i as usize)?.get(j as usize).copied()
+    }
+
+    /// Every cell and direction with plain loops, without the index helpers
+    fn reference_part1(input: &String) -> u32 {
+        let grid = parse_input(input).unwrap();
+        let mut n_xmas = 0;
+        for i in 0..grid.len() as isize {
+            for j in 0..grid[0].len() as isize {
+                for di in [-1, 0, 1] {
+                    for dj in [-1, 0, 1] {
+                        if (di, dj) != (0, 0)
+                            && "XMAS"
+                                .chars()
+                                .zip(0..)
+                                .all(|(c, d)| char_at(&grid, i + di * d, j + dj * d) == Some(c))
+                        {
+                            n_xmas += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        n_xmas
+    }
+
+    fn reference_part2(input: &String) -> u32 {
+        let grid = parse_input(input).unwrap();
+        let is_mas = |a: Option<char>, b: Option<char>| {
+            matches!((a, b), (Some('M'), Some('S')) | (Some('S'), Some('M')))
+        };
+        let mut n_x_mas = 0;
+        for i in 0..grid.len() as isize {
+            for j in 0..grid[0].len() as isize {
+                if char_at(&grid, i, j) == Some('A')
+                    && is_mas(char_at(&grid, i - 1, j - 1), char_at(&grid, i + 1, j + 1))
+                    && is_mas(char_at(&grid, i - 1, j + 1), char_at(&grid, i + 1, j - 1))
+                {
+                    n_x_mas += 1;
+                }
+            }
+        }
+
+        n_x_mas
+    }
+
+    /// Rectangular word searches, mostly made of the letters of XMAS
+    fn word_search() -> impl Strategy<Value = String> {
+        (1..12usize, 1..12usize).prop_flat_map(|(height, width)| {
+            prop::collection::vec(
+                prop::collection::vec(prop::sample::select(vec!['X', 'M', 'A', 'S', '.']), width),
+                height,
+            )
+            .prop_map(|rows| {
+                rows.iter()
+                    .map(|row| row.iter().collect::<String>())
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            })
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_part1_matches_reference(input in word_search()) {
+            prop_assert_eq!(solve_part1(&input), reference_part1(&input).to_string());
+        }
+
+        #[test]
+        fn prop_part2_matches_reference(input in word_search()) {
+            prop_assert_eq!(solve_part2(&input), reference_part2(&input).to_string());
+        }
+    }
 }
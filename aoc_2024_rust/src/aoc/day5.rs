@@ -1,6 +1,6 @@
-use crate::aoc::input::read_input_for_day;
-use std::cmp::Ordering;
-use std::collections::HashMap;
+use crate::aoc::input::{parse_at, read_input_for_day, ParseError};
+use crate::aoc::order::PrecedenceGraph;
+use std::collections::HashSet;
 
 pub fn run() {
     println!("Day 5 Solutions");
@@ -18,119 +18,103 @@ type Rules = Vec<Rule>;
 type Page = Vec<Num>;
 type Pages = Vec<Page>;
 
-fn parse_input(input: &String) -> (Rules, Pages) {
-    let rules_str = input.split("\n\n").nth(0).unwrap();
-    let pages_str = input.split("\n\n").nth(1).unwrap();
+/// The rules must order the numbers of every page without cycles, and a page
+/// can't have the same number twice.
+pub fn parse_input(input: &String) -> Result<(Rules, Pages), ParseError> {
+    let Some((rules_str, pages_str)) = input.split_once("\n\n") else {
+        return Err(ParseError::new(
+            "expected rules and pages separated by a blank line",
+        ));
+    };
+    let n_rule_lines = rules_str.split('\n').count() + 1;
 
     let rules = rules_str
         .trim()
         .split('\n')
-        .map(|line| {
-            line.split('|')
-                .take(2)
-                .map(|s| s.parse::<Num>().unwrap())
-                .collect::<Vec<Num>>()
+        .enumerate()
+        .map(|(ix, line)| match line.split_once('|') {
+            Some((a, b)) => Ok((parse_at::<Num>(a, ix + 1)?, parse_at::<Num>(b, ix + 1)?)),
+            None => Err(ParseError::at_line(
+                ix + 1,
+                format!("invalid rule {line:?}"),
+            )),
         })
-        .map(|rule| (rule[0], rule[1]))
-        .collect::<Rules>();
+        .collect::<Result<Rules, ParseError>>()?;
 
     let pages = pages_str
         .trim()
         .split('\n')
-        .map(|line| {
+        .enumerate()
+        .map(|(ix, line)| {
             line.split(',')
-                .map(|s| s.parse::<Num>().unwrap())
-                .collect::<Page>()
+                .map(|s| parse_at::<Num>(s, n_rule_lines + ix + 1))
+                .collect::<Result<Page, ParseError>>()
         })
-        .collect::<Pages>();
+        .collect::<Result<Pages, ParseError>>()?;
 
-    (rules, pages)
+    let ordering = ordering_table_from(&rules);
+    for (ix, page) in pages.iter().enumerate() {
+        if page.iter().collect::<HashSet<&Num>>().len() != page.len() {
+            return Err(ParseError::at_line(
+                n_rule_lines + ix + 1,
+                "repeated page number",
+            ));
+        }
+        if let Err(err) = ordering.sort_subset(page) {
+            return Err(ParseError::at_line(n_rule_lines + ix + 1, err.to_string()));
+        }
+    }
+
+    Ok((rules, pages))
 }
 
-type OrderingTable = HashMap<Num, Vec<Num>>;
+type OrderingTable = PrecedenceGraph<Num>;
 fn ordering_table_from(rules: &Rules) -> OrderingTable {
-    let mut table = OrderingTable::new();
-    for (num, lesser) in rules.into_iter() {
-        table
-            .entry(*num)
-            .and_modify(|entry| entry.push(*lesser))
-            .or_insert(vec![*lesser]);
-    }
-
-    table
+    OrderingTable::from_rules(rules)
 }
 
 fn in_correct_order(page: &Page, ordering: &OrderingTable) -> bool {
-    /*
-    for i in 0..page.len() {
-        let num = page[i];
-        if page[i + 1..].iter().any(|n| {
-            if let Some(lessers) = ordering.get(n) {
-                lessers.contains(&num)
-            } else {
-                false
-            }
-        }) {
-            return false;
-        }
-    }
-
-    true
-    */
-
-    !(0..page.len()).any(|i| {
-        page[i + 1..].iter().any(|n| {
-            if let Some(lessers) = ordering.get(n) {
-                lessers.contains(&page[i])
-            } else {
-                false
-            }
-        })
-    })
+    ordering.is_consistent(page)
 }
 
 fn correct_order_of(page: &Page, ordering: &OrderingTable) -> Page {
-    let mut page = page.to_vec();
-
-    page.sort_by(|a, b| {
-        if let Some(lessers) = ordering.get(b) {
-            if lessers.contains(a) {
-                Ordering::Less
-            } else {
-                Ordering::Greater
-            }
-        } else {
-            Ordering::Equal
-        }
-    });
-
-    page
+    ordering
+        .sort_subset(page)
+        .unwrap_or_else(|err| panic!("Cannot order page {page:?}: {err}"))
 }
 
-fn solve_part1(input: &String) -> String {
-    let (rules, pages) = parse_input(input);
+pub fn solve_part1(input: &String) -> String {
+    let (rules, pages) = parse_input(input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
     let ordering = ordering_table_from(&rules);
     pages
         .iter()
         .filter(|page| in_correct_order(page, &ordering))
-        .fold(0, |acc, page| acc + page[page.len() / 2])
+        .fold(0, |acc, page| acc + i64::from(page[page.len() / 2]))
         .to_string()
 }
 
-fn solve_part2(input: &String) -> String {
-    let (rules, pages) = parse_input(input);
+pub fn solve_part2(input: &String) -> String {
+    let (rules, pages) = parse_input(input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
     let ordering = ordering_table_from(&rules);
     pages
         .iter()
         .filter(|page| !in_correct_order(page, &ordering))
         .map(|page| correct_order_of(page, &ordering))
-        .fold(0, |acc, page| acc + page[page.len() / 2])
+        .fold(0, |acc, page| acc + i64::from(page[page.len() / 2]))
         .to_string()
 }
 
+/// Both answers, or why `input` can't be solved
+pub fn try_solve(input: &String) -> Result<(String, String), ParseError> {
+    parse_input(input)?;
+    Ok((solve_part1(input), solve_part2(input)))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use proptest::prelude::*;
+    use std::cmp::Ordering;
 
     const INPUT: [&str; 1] = ["
 47|53
@@ -169,16 +153,16 @@ mod test {
 
     #[test]
     fn test_parse_input() {
-        let (rules, pages) = parse_input(&get_input(0));
+        let (rules, pages) = parse_input(&get_input(0)).unwrap();
         assert_eq!(rules[0], (47, 53));
         assert_eq!(pages[0], vec![75, 47, 61, 53, 29]);
     }
 
     #[test]
     fn test_ordering_table() {
-        let (rules, _) = parse_input(&get_input(0));
+        let (rules, _) = parse_input(&get_input(0)).unwrap();
         let ordering = ordering_table_from(&rules);
-        assert_eq!(ordering[&53], [29, 13]);
+        assert_eq!(ordering.successors_of(&53), [29, 13]);
     }
 
     #[test]
@@ -190,4 +174,143 @@ mod test {
     fn test_full_part2() {
         assert_eq!(solve_part2(&get_input(0)), "123");
     }
+
+    #[test]
+    fn test_correct_order_of() {
+        let (rules, _) = parse_input(&get_input(0)).unwrap();
+        let ordering = ordering_table_from(&rules);
+        assert_eq!(
+            correct_order_of(&vec![97, 13, 75, 29, 47], &ordering),
+            vec![97, 75, 47, 29, 13]
+        );
+        // 13 and 61 are unrelated to 1 and 2, which keep their order
+        let ordering = ordering_table_from(&vec![(61, 13)]);
+        assert_eq!(
+            correct_order_of(&vec![2, 13, 1, 61], &ordering),
+            vec![2, 1, 61, 13]
+        );
+    }
+
+    #[test]
+    fn test_try_solve() {
+        assert_eq!(
+            try_solve(&String::from("1|2\n\n1,x")),
+            Err(ParseError::at_line(3, "invalid value \"x\""))
+        );
+        // The cycle can be reported from either page
+        let err = try_solve(&String::from("1|2\n2|1\n\n1,2,3\n3")).unwrap_err();
+        assert_eq!(err.line, Some(4));
+        assert!(err.message.starts_with("ordering rules contain a cycle: "));
+        assert_eq!(
+            try_solve(&String::from("1|2\n\n1,2,1")),
+            Err(ParseError::at_line(3, "repeated page number"))
+        );
+        assert!(try_solve(&String::from("1|2")).is_err());
+        assert!(try_solve(&String::from("1|2\n\n")).is_err());
+    }
+
+    /// No later number of the page has a rule putting it before an earlier one
+    fn reference_in_correct_order(page: &Page, rules: &Rules) -> bool {
+        for i in 0..page.len() {
+            for j in i + 1..page.len() {
+                if rules.contains(&(page[j], page[i])) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Only valid if the rules order every pair of numbers on the page
+    fn reference_correct_order_of(page: &Page, rules: &Rules) -> Page {
+        let mut page = page.to_vec();
+        page.sort_by(|a, b| {
+            if rules.contains(&(*a, *b)) {
+                Ordering::Less
+            } else if rules.contains(&(*b, *a)) {
+                Ordering::Greater
+            } else {
+                Ordering::Equal
+            }
+        });
+
+        page
+    }
+
+    fn reference_solve(input: &String, fix: bool) -> Num {
+        let (rules, pages) = parse_input(input).unwrap();
+        pages
+            .iter()
+            .filter(|page| reference_in_correct_order(page, &rules) != fix)
+            .map(|page| reference_correct_order_of(page, &rules))
+            .fold(0, |acc, page| acc + page[page.len() / 2])
+    }
+
+    fn to_input(rules: &Rules, pages: &Pages) -> String {
+        let rules = rules
+            .iter()
+            .map(|(a, b)| format!("{a}|{b}"))
+            .collect::<Vec<String>>();
+        let pages = pages
+            .iter()
+            .map(|page| {
+                page.iter()
+                    .map(|n| n.to_string())
+                    .collect::<Vec<String>>()
+                    .join(",")
+            })
+            .collect::<Vec<String>>();
+        format!("{}\n\n{}", rules.join("\n"), pages.join("\n"))
+    }
+
+    /// Like the puzzle input, the rules order every pair of numbers and pages
+    /// have an odd number of distinct numbers.
+    fn manual() -> impl Strategy<Value = String> {
+        prop::sample::subsequence((10..100).collect::<Page>(), 3..12)
+            .prop_shuffle()
+            .prop_flat_map(|order| {
+                let rules = (0..order.len())
+                    .flat_map(|i| (i + 1..order.len()).map(move |j| (i, j)))
+                    .map(|(i, j)| (order[i], order[j]))
+                    .collect::<Rules>();
+                let page = prop::sample::subsequence(order.clone(), 1..=order.len())
+                    .prop_filter("odd length", |page| page.len() % 2 == 1)
+                    .prop_shuffle();
+                (
+                    Just(rules).prop_shuffle(),
+                    prop::collection::vec(page, 1..8),
+                )
+            })
+            .prop_map(|(rules, pages)| to_input(&rules, &pages))
+    }
+
+    /// Rules that may leave pairs unordered or contain cycles
+    fn any_rules() -> impl Strategy<Value = (Rules, Page)> {
+        (
+            prop::collection::vec((10..16, 10..16), 1..20),
+            prop::collection::vec(10..16, 1..8),
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn prop_part1_matches_reference(input in manual()) {
+            prop_assert_eq!(solve_part1(&input), reference_solve(&input, false).to_string());
+        }
+
+        #[test]
+        fn prop_part2_matches_reference(input in manual()) {
+            prop_assert_eq!(solve_part2(&input), reference_solve(&input, true).to_string());
+        }
+
+        #[test]
+        fn prop_in_correct_order_matches_reference((rules, page) in any_rules()) {
+            let ordering = ordering_table_from(&rules);
+            prop_assert_eq!(
+                in_correct_order(&page, &ordering),
+                reference_in_correct_order(&page, &rules)
+            );
+        }
+    }
 }
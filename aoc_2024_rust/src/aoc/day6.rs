@@ -1,7 +1,19 @@
-use crate::aoc::input::read_input_for_day;
+use crate::aoc::cycle::brent;
+use crate::aoc::image::{Palette, BLACK, BLUE, RED, WHITE};
+use crate::aoc::input::{parse_grid, read_input_for_day, ParseError};
+use crate::aoc::options::Options;
+use crate::aoc::overlay::{show_overlay_if_requested, Overlay};
+use crate::aoc::recorder::{record_if_requested, FrameRecorder};
 use std::collections::HashSet;
 
 pub fn run() {
+    run_with(&Options::default());
+}
+
+/// `--record=<path>` records the guard walk of part 1, see `record_if_requested`.
+/// `--overlay[=<path>]` shows the walk and the obstacles that make the guard
+/// loop, see `show_overlay_if_requested`.
+pub fn run_with(options: &Options) {
     println!("Day 6 Solutions");
     println!("---------------");
     let input = read_input_for_day(6);
@@ -9,17 +21,31 @@ pub fn run() {
     let part2 = solve_part2(&input);
     println!("\tPart1: {part1}");
     println!("\tPart2: {part2}");
+
+    record_if_requested(options, "day6", |recorder| record_walk(&input, recorder));
+    show_overlay_if_requested(options, || walk_overlay(&input));
 }
 
 type Cell = char;
 type Row = Vec<Cell>;
 type Grid = Vec<Row>;
 
-fn parse_input(input: &String) -> Grid {
-    input
-        .split('\n')
-        .map(|line| line.chars().collect::<Row>())
-        .collect::<Grid>()
+const GUARDS: [Cell; 4] = ['v', '<', '^', '>'];
+
+/// A rectangular lab with exactly one guard, who must walk out of it in the end
+pub fn parse_input(input: &String) -> Result<Grid, ParseError> {
+    let grid = parse_grid(input)?;
+    let n_guards = grid.iter().flatten().filter(|c| GUARDS.contains(c)).count();
+    if n_guards != 1 {
+        return Err(ParseError::new(format!(
+            "expected one guard, found {n_guards}"
+        )));
+    }
+    if !guard_leaves(&grid) {
+        return Err(ParseError::new("the guard never leaves the lab"));
+    }
+
+    Ok(grid)
 }
 
 type Coord = (usize, usize);
@@ -39,10 +65,9 @@ impl Pos {
 }
 
 fn find_start_position(grid: &Grid) -> Pos {
-    let guard_positions = vec!['v', '<', '^', '>'];
     for i in 0..grid.len() {
         for j in 0..grid[0].len() {
-            if guard_positions.contains(&grid[i][j]) {
+            if GUARDS.contains(&grid[i][j]) {
                 return Pos::new((i, j), grid[i][j]);
             }
         }
@@ -100,17 +125,42 @@ fn exits_grid(pos: &Pos, grid: &Grid) -> bool {
     }
 }
 
-fn solve_part1(input: &String) -> String {
-    let grid = parse_input(input);
+/// Coords of the guard until it leaves the grid, repeated when it turns in place
+fn walk_of(grid: &Grid) -> Vec<Coord> {
+    let mut pos = find_start_position(grid);
+    let mut coords = vec![pos.coord];
+    while !exits_grid(&pos, grid) {
+        pos = take_step_or_turn(&pos, grid);
+        coords.push(pos.coord);
+    }
+
+    coords
+}
+
+pub fn solve_part1(input: &String) -> String {
+    let grid = parse_input(input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
+    let unique_coords = HashSet::<Coord>::from_iter(walk_of(&grid));
+
+    unique_coords.len().to_string()
+}
+
+fn record_walk(input: &String, recorder: &mut FrameRecorder) {
+    let palette = Palette::new(WHITE)
+        .with('#', BLACK)
+        .with('X', BLUE)
+        .with('^', RED)
+        .with('>', RED)
+        .with('v', RED)
+        .with('<', RED);
+    let mut grid = parse_input(input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
     let mut pos = find_start_position(&grid);
-    let mut unique_coords = HashSet::<Coord>::new();
-    unique_coords.insert(pos.coord);
+    recorder.push_grid(&grid, &palette);
     while !exits_grid(&pos, &grid) {
+        grid[pos.coord.0][pos.coord.1] = 'X';
         pos = take_step_or_turn(&pos, &grid);
-        unique_coords.insert(pos.coord);
+        grid[pos.coord.0][pos.coord.1] = pos.dir;
+        recorder.push_grid(&grid, &palette);
     }
-
-    unique_coords.len().to_string()
 }
 
 fn coords_around(coord: &Coord, grid: &Grid) -> Vec<Coord> {
@@ -133,8 +183,20 @@ fn coords_around(coord: &Coord, grid: &Grid) -> Vec<Coord> {
     coords
 }
 
-fn solve_part2(input: &String) -> String {
-    let grid = parse_input(input);
+/// The guard's walk, ending in `None` for good once the guard leaves the grid
+fn step_on(pos: &Option<Pos>, grid: &Grid) -> Option<Pos> {
+    match pos {
+        Some(pos) if !exits_grid(pos, grid) => Some(take_step_or_turn(pos, grid)),
+        _ => None,
+    }
+}
+
+fn walk_loops(pos: Pos, grid: &Grid) -> bool {
+    brent(Some(pos), |pos| step_on(pos, grid)).state.is_some()
+}
+
+fn find_new_obstacles(input: &String) -> HashSet<Coord> {
+    let grid = parse_input(input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
     let mut pos = find_start_position(&grid);
     let mut unique_coords = HashSet::<Coord>::new();
     unique_coords.insert(pos.coord);
@@ -159,23 +221,37 @@ fn solve_part2(input: &String) -> String {
 
     let mut new_obstacles = HashSet::<Coord>::new();
     for coord in candidates {
-        let mut grid = parse_input(input);
+        let mut grid = grid.clone();
         grid[coord.0][coord.1] = '#';
-        let mut pos = find_start_position(&grid);
-        let mut unique_pos = HashSet::<Pos>::new();
-        unique_pos.insert(pos.clone());
-        while !exits_grid(&pos, &grid) {
-            pos = take_step_or_turn(&pos, &grid);
-            if unique_pos.contains(&pos) {
-                new_obstacles.insert(coord);
-                break;
-            }
-
-            unique_pos.insert(pos.clone());
+        let pos = find_start_position(&grid);
+        if walk_loops(pos, &grid) {
+            new_obstacles.insert(coord);
         }
     }
 
-    new_obstacles.len().to_string()
+    new_obstacles
+}
+
+/// Whether the guard on `grid` walks out of it instead of going in circles
+pub fn guard_leaves(grid: &Grid) -> bool {
+    !walk_loops(find_start_position(grid), grid)
+}
+
+pub fn solve_part2(input: &String) -> String {
+    find_new_obstacles(input).len().to_string()
+}
+
+fn walk_overlay(input: &String) -> Overlay {
+    let grid = parse_input(input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
+    Overlay::new(&grid)
+        .path(walk_of(&grid), BLUE)
+        .points(find_new_obstacles(input), Some('O'), RED)
+}
+
+/// Both answers, or why `input` can't be solved
+pub fn try_solve(input: &String) -> Result<(String, String), ParseError> {
+    parse_input(input)?;
+    Ok((solve_part1(input), solve_part2(input)))
 }
 
 #[cfg(test)]
@@ -200,7 +276,7 @@ mod test {
 
     #[test]
     fn test_parse_input() {
-        let grid = parse_input(&get_input(0));
+        let grid = parse_input(&get_input(0)).unwrap();
 
         assert_eq!(grid[0][0], '.');
         assert_eq!(grid[3][2], '#');
@@ -208,14 +284,14 @@ mod test {
 
     #[test]
     fn test_find_start_position() {
-        let grid = parse_input(&get_input(0));
+        let grid = parse_input(&get_input(0)).unwrap();
         let pos = find_start_position(&grid);
         assert_eq!(pos.coord, (6, 4));
     }
 
     #[test]
     fn test_exits_grid() {
-        let grid = parse_input(&get_input(0));
+        let grid = parse_input(&get_input(0)).unwrap();
         for pos in vec![
             Pos::new((0, 0), '<'),
             Pos::new((0, 0), '^'),
@@ -236,7 +312,7 @@ mod test {
 
     #[test]
     fn test_take_step_or_turn() {
-        let grid = parse_input(&get_input(0));
+        let grid = parse_input(&get_input(0)).unwrap();
         // Step
         for (start, end) in vec![
             (Pos::new((0, 0), '>'), Pos::new((0, 1), '>')),
@@ -260,6 +336,55 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_walk_loops() {
+        let mut grid = parse_input(&get_input(0)).unwrap();
+        let pos = find_start_position(&grid);
+        assert!(!walk_loops(pos.clone(), &grid));
+
+        grid[6][3] = '#';
+        assert!(walk_loops(pos, &grid));
+    }
+
+    #[test]
+    fn test_record_walk() {
+        let mut recorder = FrameRecorder::new(1, 1);
+        record_walk(&get_input(0), &mut recorder);
+        // The start, then one frame per step or turn
+        assert_eq!(recorder.frames().len(), 55);
+        let last = recorder.frames().last().unwrap();
+        assert_eq!(last.get((4, 6)), BLUE);
+        assert_eq!(last.get((7, 9)), RED);
+        assert_eq!(last.get((0, 0)), WHITE);
+    }
+
+    #[test]
+    fn test_walk_overlay() {
+        let cells = walk_overlay(&get_input(0)).cells();
+        assert_eq!(cells[5][4], ('↑', Some(BLUE)));
+        assert_eq!(cells[1][4], ('→', Some(BLUE)));
+        assert_eq!(cells[9][7], ('O', Some(RED)));
+        assert_eq!(cells[0][4], ('#', None));
+        let n_obstacles = cells.iter().flatten().filter(|(c, _)| *c == 'O').count();
+        assert_eq!(n_obstacles, 6);
+    }
+
+    #[test]
+    fn test_try_solve() {
+        assert_eq!(
+            try_solve(&String::from("..\n.^\n>.")),
+            Err(ParseError::new("expected one guard, found 2"))
+        );
+        assert_eq!(
+            try_solve(&String::from(".#..\n.^.#\n#...\n..#.")),
+            Err(ParseError::new("the guard never leaves the lab"))
+        );
+        assert_eq!(
+            try_solve(&String::from("...\n.>.")),
+            Ok(("2".to_string(), "0".to_string()))
+        );
+    }
+
     #[test]
     fn test_full_part1() {
         assert_eq!(solve_part1(&get_input(0)), "41");
@@ -1,5 +1,4 @@
-use crate::aoc::input::read_input_for_day;
-use itertools::Itertools;
+use crate::aoc::input::{parse_at, read_input_for_day, ParseError};
 use std::collections::HashSet;
 
 pub fn run() {
@@ -17,62 +16,57 @@ type Nums = Vec<Num>;
 type Calibration = (Num, Nums);
 type Calibrations = Vec<Calibration>;
 
-fn line_to_calibration(line: &str) -> Calibration {
-    let mut parts = line.split(':');
-    let test_val = parts.nth(0).unwrap().parse::<Num>().unwrap();
-    let nums = parts
-        .nth(0)
-        .unwrap()
+/// Test values must fit in 64 bits, so the sum of many of them can't overflow
+fn line_to_calibration(line: &str, line_num: usize) -> Result<Calibration, ParseError> {
+    let Some((test_val, nums)) = line.split_once(':') else {
+        return Err(ParseError::at_line(line_num, "missing ':'"));
+    };
+    let test_val = Num::from(parse_at::<u64>(test_val, line_num)?);
+    let nums = nums
         .trim()
         .split(' ')
-        .map(|part| part.parse::<Num>().unwrap())
-        .collect::<Nums>();
+        .map(|part| parse_at::<Num>(part, line_num))
+        .collect::<Result<Nums, ParseError>>()?;
 
-    (test_val, nums)
+    Ok((test_val, nums))
 }
-fn parse_input(input: &String) -> Calibrations {
+
+pub fn parse_input(input: &String) -> Result<Calibrations, ParseError> {
     input
         .split('\n')
-        .map(line_to_calibration)
-        .collect::<Calibrations>()
+        .enumerate()
+        .map(|(ix, line)| line_to_calibration(line, ix + 1))
+        .collect::<Result<Calibrations, ParseError>>()
 }
 
 type Op = char;
 
-fn apply(op: Op, a: Num, b: Num) -> Num {
+/// `None` on overflow
+fn apply(op: Op, a: Num, b: Num) -> Option<Num> {
     match op {
-        '+' => a + b,
-        '*' => a * b,
+        '+' => a.checked_add(b),
+        '*' => a.checked_mul(b),
         _ => panic!("Unsupported operation: {op}"),
     }
 }
 
+/// Operations are applied left to right, so the results after each number only
+/// depend on the results before it. Results over `test_val` are dropped.
 fn operation_results_contains(test_val: Num, nums: &Nums, ops: Vec<char>) -> bool {
-    let mut unique_ops = HashSet::<Vec<char>>::new();
-    for op_iter in ops.into_iter().combinations_with_replacement(nums.len() - 1) {
-        for op_perm in op_iter.clone().into_iter().permutations(op_iter.len()) {
-            if !unique_ops.contains(&op_perm) {
-                let mut result = nums[0];
-                for (num, op) in nums.iter().skip(1).zip(op_perm.clone()) {
-                    result = apply(op, result, *num);
-                    if result > test_val {
-                        break;
-                    }
-                }
-                unique_ops.insert(op_perm.clone());
-
-                if result == test_val {
-                    return true;
-                }
-            }
-        }
+    let mut results = HashSet::from([nums[0]]);
+    for num in nums.iter().skip(1) {
+        results = results
+            .iter()
+            .flat_map(|result| ops.iter().filter_map(|op| apply(*op, *result, *num)))
+            .filter(|result| *result <= test_val)
+            .collect();
     }
 
-    false
+    results.contains(&test_val)
 }
 
-fn solve_part1(input: &String) -> String {
-    let calibrations = parse_input(input);
+pub fn solve_part1(input: &String) -> String {
+    let calibrations = parse_input(input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
     let mut calibration_result = 0;
     for (test_val, nums) in calibrations {
         calibration_result += if operation_results_contains(test_val, &nums, vec!['*', '+']) {
@@ -85,10 +79,16 @@ fn solve_part1(input: &String) -> String {
     calibration_result.to_string()
 }
 
-fn solve_part2(_input: &String) -> String {
+pub fn solve_part2(_input: &String) -> String {
     String::new()
 }
 
+/// Both answers, or why `input` can't be solved
+pub fn try_solve(input: &String) -> Result<(String, String), ParseError> {
+    parse_input(input)?;
+    Ok((solve_part1(input), solve_part2(input)))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -110,13 +110,13 @@ mod test {
 
     #[test]
     fn test_parse_input() {
-        let parsed = parse_input(&get_input(0));
+        let parsed = parse_input(&get_input(0)).unwrap();
         assert_eq!(parsed[0], (190, vec![10, 19]));
     }
 
     #[test]
     fn test_operation_results() {
-        let parsed = parse_input(&get_input(0));
+        let parsed = parse_input(&get_input(0)).unwrap();
         let (test_val, nums) = &parsed[0];
         assert!(operation_results_contains(*test_val, &nums, vec!['+', '*']));
     }
@@ -130,4 +130,18 @@ mod test {
     fn test_full_part2() {
         assert_eq!(solve_part2(&get_input(0)), "");
     }
+
+    #[test]
+    fn test_try_solve() {
+        assert_eq!(
+            try_solve(&String::from("190: 10 19\n3267 81 40 27")),
+            Err(ParseError::at_line(2, "missing ':'"))
+        );
+        // Too many numbers to try every combination of operations
+        let input = format!("40: {}", vec!["1"; 200].join(" "));
+        assert_eq!(try_solve(&input).unwrap().0, "40");
+        // Products that overflow
+        let input = format!("5: {} {} 5", u128::MAX, u128::MAX);
+        assert_eq!(try_solve(&input).unwrap().0, "0");
+    }
 }
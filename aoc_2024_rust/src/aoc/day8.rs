@@ -1,8 +1,17 @@
-use crate::aoc::input::read_input_for_day;
+use crate::aoc::image::RED;
+use crate::aoc::input::{parse_grid, read_input_for_day, ParseError};
+use crate::aoc::options::Options;
+use crate::aoc::overlay::{show_overlay_if_requested, Overlay};
 use itertools::Itertools;
 use std::collections::{HashMap, HashSet};
 
 pub fn run() {
+    run_with(&Options::default());
+}
+
+/// `--overlay[=<path>]` shows the antinodes of part 1, or of part 2 with
+/// `--part=2`, see `show_overlay_if_requested`.
+pub fn run_with(options: &Options) {
     println!("Day 8 Solutions");
     println!("---------------");
     let input = read_input_for_day(8);
@@ -10,6 +19,9 @@ pub fn run() {
     let part2 = solve_part2(&input);
     println!("\tPart1: {part1}");
     println!("\tPart2: {part2}");
+
+    let part = options.part(1);
+    show_overlay_if_requested(options, || antinodes_overlay(&input, part));
 }
 
 type Loc = (isize, isize);
@@ -18,11 +30,8 @@ type Node = char;
 type Antenna = Node;
 type Antennas = HashMap<Antenna, Locs>;
 type Map = Vec<Vec<Antenna>>;
-fn parse_input(input: &String) -> Map {
-    input
-        .split('\n')
-        .map(|line| line.chars().collect::<Vec<Antenna>>())
-        .collect::<Map>()
+pub fn parse_input(input: &String) -> Result<Map, ParseError> {
+    parse_grid(input)
 }
 
 fn find_antennas_in(map: &Map) -> Antennas {
@@ -120,8 +129,8 @@ fn calc_all_antinodes_2_of(antennas: &Antennas, map: &Map) -> Locs {
         .collect::<Locs>()
 }
 
-fn solve_part1(input: &String) -> String {
-    let map = parse_input(input);
+pub fn solve_part1(input: &String) -> String {
+    let map = parse_input(input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
     let antennas = find_antennas_in(&map);
     let antinodes = calc_all_antinodes_of(&antennas, &map);
     HashSet::<Loc>::from_iter(antinodes.into_iter())
@@ -129,8 +138,8 @@ fn solve_part1(input: &String) -> String {
         .to_string()
 }
 
-fn solve_part2(input: &String) -> String {
-    let map = parse_input(input);
+pub fn solve_part2(input: &String) -> String {
+    let map = parse_input(input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
     let antennas = find_antennas_in(&map);
     let antinodes = calc_all_antinodes_2_of(&antennas, &map);
 
@@ -139,6 +148,31 @@ fn solve_part2(input: &String) -> String {
         .to_string()
 }
 
+fn antinodes_overlay(input: &String, part: u8) -> Overlay {
+    let map = parse_input(input).unwrap_or_else(|err| panic!("Invalid input, {err}"));
+    let antennas = find_antennas_in(&map);
+    let antinodes = if part == 2 {
+        calc_all_antinodes_2_of(&antennas, &map)
+    } else {
+        calc_all_antinodes_of(&antennas, &map)
+    };
+
+    // Antinodes on antennas keep the antenna
+    let (empty, on_antennas): (Vec<_>, Vec<_>) = antinodes
+        .into_iter()
+        .map(|(i, j)| (i as usize, j as usize))
+        .partition(|(i, j)| map[*i][*j] == '.');
+    Overlay::new(&map)
+        .points(empty, Some('#'), RED)
+        .points(on_antennas, None, RED)
+}
+
+/// Both answers, or why `input` can't be solved
+pub fn try_solve(input: &String) -> Result<(String, String), ParseError> {
+    parse_input(input)?;
+    Ok((solve_part1(input), solve_part2(input)))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -214,19 +248,41 @@ T....#....
 
     #[test]
     fn test_parse_input() {
-        let map = parse_input(&get_input(0));
+        let map = parse_input(&get_input(0)).unwrap();
         assert_eq!(map[0][0], '.');
         assert_eq!(map[3][4], 'a');
     }
 
     #[test]
     fn test_calc_all_antinodes_of() {
-        let map = parse_input(&get_input(0));
+        let map = parse_input(&get_input(0)).unwrap();
         let antennas = find_antennas_in(&map);
         let antinodes = calc_all_antinodes_of(&antennas, &map);
         assert!(antinodes.contains(&(1, 3)));
     }
 
+    #[test]
+    fn test_antinodes_overlay() {
+        let cells = antinodes_overlay(&get_input(4).replace('#', "."), 2).cells();
+        assert_eq!(cells[0][0], ('T', Some(RED)));
+        assert_eq!(cells[0][5], ('#', Some(RED)));
+        assert_eq!(cells[0][1], ('.', None));
+        let n_antinodes = cells.iter().flatten().filter(|(_, c)| c.is_some()).count();
+        assert_eq!(n_antinodes, 9);
+    }
+
+    #[test]
+    fn test_try_solve() {
+        assert_eq!(
+            try_solve(&String::from("a..\n.a")),
+            Err(ParseError::at_line(2, "expected 3 cells, found 2"))
+        );
+        assert_eq!(
+            try_solve(&String::from("a.a.")),
+            Ok(("0".to_string(), "2".to_string()))
+        );
+    }
+
     #[test]
     fn test_full_part1() {
         assert_eq!(solve_part1(&get_input(0)), "2");
@@ -0,0 +1,530 @@
+use crate::aoc::day6;
+use crate::aoc::options::Options;
+use std::ops::Range;
+
+/// Small seeded PRNG (SplitMix64), so generated inputs can be reproduced
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `range`, which must not be empty
+    pub fn range(&mut self, range: Range<i64>) -> i64 {
+        assert!(!range.is_empty(), "Empty range: {range:?}");
+        let span = range.end.abs_diff(range.start);
+        range.start.wrapping_add((self.next_u64() % span) as i64)
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        self.range(0..n as i64) as usize
+    }
+
+    pub fn chance(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64) < p * (1u64 << 53) as f64
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+type Grid = Vec<Vec<char>>;
+
+fn grid_to_string(grid: &Grid) -> String {
+    grid.iter()
+        .map(|row| row.iter().collect::<String>())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn random_empty_cell(rng: &mut Rng, grid: &Grid) -> (usize, usize) {
+    loop {
+        let (i, j) = (rng.below(grid.len()), rng.below(grid[0].len()));
+        if grid[i][j] == '.' {
+            return (i, j);
+        }
+    }
+}
+
+/// `n_lines` pairs of 5 digit location ids, some of them shared between the lists
+pub fn day1(rng: &mut Rng, n_lines: usize) -> String {
+    let left = (0..n_lines)
+        .map(|_| rng.range(10000..100000))
+        .collect::<Vec<i64>>();
+    (0..n_lines)
+        .map(|ix| {
+            let right = if rng.chance(0.3) {
+                *rng.pick(&left)
+            } else {
+                rng.range(10000..100000)
+            };
+            format!("{}   {right}", left[ix])
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Reports of 5 to `max_levels` levels, mostly monotonic with steps of 1 to 3
+/// and a bad level now and then.
+pub fn day2(rng: &mut Rng, n_reports: usize, max_levels: usize) -> String {
+    (0..n_reports)
+        .map(|_| {
+            let n_levels = rng.range(5..max_levels.max(5) as i64 + 1);
+            let sign = if rng.chance(0.5) { 1 } else { -1 };
+            let mut level = rng.range(1..100);
+            let mut levels = vec![level];
+            for _ in 1..n_levels {
+                level += if rng.chance(0.1) {
+                    rng.range(-4..8) * sign
+                } else {
+                    rng.range(1..4) * sign
+                };
+                levels.push(level);
+            }
+            levels
+                .iter()
+                .map(|level| level.to_string())
+                .collect::<Vec<String>>()
+                .join(" ")
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// About `length` characters of `mul(x,y)`, `do()` and `don't()` instructions,
+/// broken instructions and noise.
+pub fn day3(rng: &mut Rng, length: usize) -> String {
+    const NOISE: &[char] = &[
+        'x', 'a', 'm', 'u', 'l', 'd', 'o', 'n', '\'', 't', ' ', '!', '@', '#', '$', '%', '^', '&',
+        '*', '(', ')', '[', ']', '{', '}', '<', '>', ',', '?', ':', ';', '+', '-', '/', '_',
+    ];
+    let mut memory = String::new();
+    while memory.len() < length {
+        let fragment = match rng.below(10) {
+            0..=2 => format!("mul({},{})", rng.range(0..1000), rng.range(0..1000)),
+            3 => format!("mul({},{}]", rng.range(0..1000), rng.range(0..1000)),
+            4 => format!("mul( {},{})", rng.range(0..100), rng.range(0..100)),
+            5 => rng
+                .pick(&["do()", "don't()", "undo()", "do", "don't"])
+                .to_string(),
+            _ => (0..rng.range(1..6)).map(|_| *rng.pick(NOISE)).collect(),
+        };
+        memory.push_str(&fragment);
+    }
+
+    memory
+}
+
+/// A `width` x `height` word search made only of the letters of XMAS
+pub fn day4(rng: &mut Rng, width: usize, height: usize) -> String {
+    let grid = (0..height)
+        .map(|_| {
+            (0..width)
+                .map(|_| *rng.pick(&['X', 'M', 'A', 'S']))
+                .collect()
+        })
+        .collect::<Grid>();
+    grid_to_string(&grid)
+}
+
+/// Page ordering rules between `n_pages` distinct page numbers in a consistent
+/// order, followed by `n_updates` updates with an odd number of pages, about
+/// half of them in the right order.
+pub fn day5(rng: &mut Rng, n_pages: usize, n_updates: usize) -> String {
+    let mut order = (10..100).collect::<Vec<i64>>();
+    rng.shuffle(&mut order);
+    order.truncate(n_pages.clamp(3, 90));
+
+    let mut rules = Vec::new();
+    for i in 0..order.len() {
+        for j in i + 1..order.len() {
+            rules.push(format!("{}|{}", order[i], order[j]));
+        }
+    }
+    rng.shuffle(&mut rules);
+
+    let updates = (0..n_updates)
+        .map(|_| {
+            let mut pages = order.clone();
+            rng.shuffle(&mut pages);
+            let max_len = (order.len() - 1) / 2;
+            pages.truncate(2 * rng.range(1..max_len as i64 + 1) as usize + 1);
+            if rng.chance(0.5) {
+                pages.sort_by_key(|page| order.iter().position(|p| p == page));
+            }
+            pages
+                .iter()
+                .map(|page| page.to_string())
+                .collect::<Vec<String>>()
+                .join(",")
+        })
+        .collect::<Vec<String>>();
+
+    format!("{}\n\n{}", rules.join("\n"), updates.join("\n"))
+}
+
+/// A lab with obstacles on about `density` of the cells and a guard facing up,
+/// who always walks out of the lab in the end.
+pub fn day6(rng: &mut Rng, width: usize, height: usize, density: f64) -> String {
+    // Dense labs rarely let the guard out, so give up at some point
+    const MAX_ATTEMPTS: usize = 1000;
+    assert!(
+        width > 0 && height > 0,
+        "The lab needs a width and height of at least 1, got {width}x{height}"
+    );
+    assert!(
+        (0.0..1.0).contains(&density),
+        "The obstacle density must be at least 0 and below 1, got {density}"
+    );
+    for _ in 0..MAX_ATTEMPTS {
+        let mut grid = (0..height)
+            .map(|_| {
+                (0..width)
+                    .map(|_| if rng.chance(density) { '#' } else { '.' })
+                    .collect()
+            })
+            .collect::<Grid>();
+        if !grid.iter().flatten().any(|cell| *cell == '.') {
+            continue;
+        }
+        let (i, j) = random_empty_cell(rng, &grid);
+        grid[i][j] = '^';
+        if day6::guard_leaves(&grid) {
+            return grid_to_string(&grid);
+        }
+    }
+
+    panic!(
+        "No lab the guard leaves in {MAX_ATTEMPTS} attempts, try a lower density than {density}"
+    );
+}
+
+/// A topographic map with up to `n_trails` hiking trails from 0 to 9 over random
+/// heights. Trails don't cross each other, so they all stay intact.
+pub fn day10(rng: &mut Rng, width: usize, height: usize, n_trails: usize) -> String {
+    let (width, height) = (width.max(2), height.max(2));
+    let mut topo = (0..height)
+        .map(|_| (0..width).map(|_| rng.range(0..10) as u8).collect())
+        .collect::<Vec<Vec<u8>>>();
+    let mut carved = vec![vec![false; width]; height];
+    let mut n_carved = 0;
+    for _ in 0..100 * n_trails {
+        if n_carved == n_trails {
+            break;
+        }
+        let mut trail = vec![(rng.below(height), rng.below(width))];
+        while trail.len() < 10 {
+            let (i, j) = trail[trail.len() - 1];
+            let neighbors = [(0, 1), (1, 0), (0, -1), (-1, 0)]
+                .iter()
+                .map(|(di, dj)| ((i as i64 + di) as usize, (j as i64 + dj) as usize))
+                .filter(|(i, j)| *i < height && *j < width && !trail.contains(&(*i, *j)))
+                .collect::<Vec<(usize, usize)>>();
+            if neighbors.is_empty() {
+                break;
+            }
+            trail.push(*rng.pick(&neighbors));
+        }
+        if trail.len() == 10 && trail.iter().all(|(i, j)| !carved[*i][*j]) {
+            for (h, (i, j)) in trail.into_iter().enumerate() {
+                topo[i][j] = h as u8;
+                carved[i][j] = true;
+            }
+            n_carved += 1;
+        }
+    }
+
+    topo.iter()
+        .map(|row| row.iter().map(|h| h.to_string()).collect::<String>())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Equations of 2 to `max_operands` operands below 100, about half of them
+/// made true by some mix of `+`, `*` and `||`.
+pub fn day7(rng: &mut Rng, n_equations: usize, max_operands: usize) -> String {
+    (0..n_equations)
+        .map(|_| {
+            let n_operands = rng.range(2..max_operands.max(2) as i64 + 1);
+            let operands = (0..n_operands)
+                .map(|_| rng.range(1..100) as u64)
+                .collect::<Vec<u64>>();
+            let solved = operands[1..]
+                .iter()
+                .try_fold(operands[0], |acc, n| match rng.below(3) {
+                    0 => acc.checked_add(*n),
+                    1 => acc.checked_mul(*n),
+                    _ => format!("{acc}{n}").parse().ok(),
+                });
+            let test_value = match solved {
+                Some(value) if rng.chance(0.5) => value,
+                _ => rng.range(1..1_000_000) as u64,
+            };
+            let operands = operands
+                .iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<String>>()
+                .join(" ");
+            format!("{test_value}: {operands}")
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// A `width` x `height` map with `n_antennas` antennas of a handful of frequencies
+pub fn day8(rng: &mut Rng, width: usize, height: usize, n_antennas: usize) -> String {
+    const FREQUENCIES: &[char] = &['0', '1', '2', 'a', 'b', 'A', 'B', 'Z'];
+    let mut grid = vec![vec!['.'; width.max(1)]; height.max(1)];
+    for _ in 0..n_antennas.min(width * height) {
+        let (i, j) = random_empty_cell(rng, &grid);
+        grid[i][j] = *rng.pick(FREQUENCIES);
+    }
+
+    grid_to_string(&grid)
+}
+
+/// `n_stones` stones engraved with numbers below 10 million
+pub fn day11(rng: &mut Rng, n_stones: usize) -> String {
+    (0..n_stones)
+        .map(|_| rng.range(0..10_000_000).to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// `n_robots` robots somewhere on a `width` x `height` floor, with velocities
+/// of up to a floor size per tick.
+pub fn day14(rng: &mut Rng, n_robots: usize, width: usize, height: usize) -> String {
+    let (width, height) = (width as i64, height as i64);
+    (0..n_robots)
+        .map(|_| {
+            format!(
+                "p={},{} v={},{}",
+                rng.range(0..width),
+                rng.range(0..height),
+                rng.range(1 - width..width),
+                rng.range(1 - height..height)
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// A walled warehouse with some inner walls, boxes on about a quarter of the
+/// floor and the robot, followed by `n_moves` moves in lines of 70.
+pub fn day15(rng: &mut Rng, width: usize, height: usize, n_moves: usize) -> String {
+    let (width, height) = (width.max(3), height.max(3));
+    let mut grid = (0..height)
+        .map(|i| {
+            (0..width)
+                .map(|j| {
+                    if i == 0 || j == 0 || i == height - 1 || j == width - 1 || rng.chance(0.05) {
+                        '#'
+                    } else if rng.chance(0.25) {
+                        'O'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect()
+        })
+        .collect::<Grid>();
+    // Make sure there is room for the robot
+    let (i, j) = (
+        rng.range(1..height as i64 - 1),
+        rng.range(1..width as i64 - 1),
+    );
+    grid[i as usize][j as usize] = '@';
+
+    let moves = (0..n_moves)
+        .map(|_| *rng.pick(&['^', '>', 'v', '<']))
+        .collect::<Vec<char>>();
+    let moves = moves
+        .chunks(70)
+        .map(|line| line.iter().collect::<String>())
+        .collect::<Vec<String>>();
+
+    format!("{}\n\n{}", grid_to_string(&grid), moves.join("\n"))
+}
+
+/// Generates an input for `day` from `--seed` (default 1), sized by `--size`
+/// and, for maps, `--width` and `--height`.
+pub fn generate(day: usize, options: &Options) -> String {
+    let mut rng = Rng::new(options.parsed("seed").unwrap_or(1));
+    let size = |default: usize| options.parsed::<usize>("size").unwrap_or(default);
+    let width = |default: usize| options.parsed::<usize>("width").unwrap_or(default);
+    let height = |default: usize| options.parsed::<usize>("height").unwrap_or(default);
+    match day {
+        1 => day1(&mut rng, size(1000)),
+        2 => day2(&mut rng, size(1000), options.parsed("levels").unwrap_or(8)),
+        3 => day3(&mut rng, size(20000)),
+        4 => day4(&mut rng, width(140), height(140)),
+        5 => day5(&mut rng, options.parsed("pages").unwrap_or(49), size(200)),
+        6 => day6(
+            &mut rng,
+            width(130),
+            height(130),
+            options.parsed("density").unwrap_or(0.06),
+        ),
+        7 => day7(
+            &mut rng,
+            size(850),
+            options.parsed("operands").unwrap_or(12),
+        ),
+        8 => day8(&mut rng, width(50), height(50), size(200)),
+        10 => day10(&mut rng, width(50), height(50), size(100)),
+        11 => day11(&mut rng, size(8)),
+        14 => day14(&mut rng, size(500), width(101), height(103)),
+        15 => day15(&mut rng, width(50), height(50), size(20000)),
+        _ => panic!("No input generator for day {day}"),
+    }
+}
+
+/// `gen <day> [options]` prints a generated input, see `generate`.
+pub fn run(args: &[String]) {
+    let Some(day) = args.first().and_then(|day| day.parse::<usize>().ok()) else {
+        panic!("Usage: gen <day> [--seed=N] [--size=N] [--width=N] [--height=N]");
+    };
+    println!("{}", generate(day, &Options::parse(&args[1..])));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rng() {
+        let mut rng = Rng::new(1);
+        let first = (0..5).map(|_| rng.next_u64()).collect::<Vec<u64>>();
+        let mut rng = Rng::new(1);
+        assert_eq!(first, (0..5).map(|_| rng.next_u64()).collect::<Vec<u64>>());
+        for _ in 0..1000 {
+            assert!((-3..5).contains(&rng.range(-3..5)));
+        }
+        let mut items = (0..10).collect::<Vec<i32>>();
+        rng.shuffle(&mut items);
+        items.sort();
+        assert_eq!(items, (0..10).collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn test_generate_is_seeded() {
+        let options = |seed: &str| Options::parse(&[format!("--seed={seed}")]);
+        for day in [1, 2, 3, 4, 5, 6, 7, 8, 10, 11, 14, 15] {
+            assert_eq!(generate(day, &options("7")), generate(day, &options("7")));
+            assert_ne!(generate(day, &options("7")), generate(day, &options("8")));
+        }
+    }
+
+    #[test]
+    fn test_generated_inputs_are_valid() {
+        let options = Options::parse(&[
+            "--size=30".to_string(),
+            "--width=20".to_string(),
+            "--height=20".to_string(),
+        ]);
+        for day in [1, 2, 3, 4, 5, 6, 7, 8, 10, 11, 14, 15] {
+            let input = generate(day, &options);
+            if let Err(err) = crate::aoc::try_solve(day, &input) {
+                panic!("Day {day} generated an invalid input, {err}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_day1() {
+        let input = day1(&mut Rng::new(3), 20);
+        assert_eq!(input.lines().count(), 20);
+        assert!(input
+            .lines()
+            .all(|line| line.split("   ").all(|id| id.len() == 5)));
+    }
+
+    #[test]
+    fn test_day5() {
+        let input = day5(&mut Rng::new(3), 7, 10);
+        let (rules, updates) = input.split_once("\n\n").unwrap();
+        // Every pair of pages is ordered
+        assert_eq!(rules.lines().count(), 7 * 6 / 2);
+        assert_eq!(updates.lines().count(), 10);
+        assert!(updates
+            .lines()
+            .all(|update| update.split(',').count() % 2 == 1));
+    }
+
+    #[test]
+    fn test_day6() {
+        let input = day6(&mut Rng::new(3), 12, 8, 0.15);
+        assert_eq!(input.lines().count(), 8);
+        assert!(input.lines().all(|line| line.len() == 12));
+        assert_eq!(input.matches('^').count(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "The obstacle density must be at least 0 and below 1, got 1")]
+    fn test_day6_full_lab() {
+        day6(&mut Rng::new(1), 10, 10, 1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "The lab needs a width and height of at least 1, got 0x10")]
+    fn test_day6_empty_lab() {
+        day6(&mut Rng::new(1), 0, 10, 0.1);
+    }
+
+    #[test]
+    fn test_day7() {
+        let input = day7(&mut Rng::new(3), 50, 6);
+        assert_eq!(input.lines().count(), 50);
+        assert!(input.lines().all(|line| {
+            let (_, operands) = line.split_once(": ").unwrap();
+            (2..=6).contains(&operands.split(' ').count())
+        }));
+    }
+
+    #[test]
+    fn test_day10() {
+        let input = day10(&mut Rng::new(3), 20, 20, 8);
+        assert_eq!(input.lines().count(), 20);
+        // At least one zero per trail
+        let topo = input
+            .lines()
+            .map(|line| line.bytes().map(|b| b - b'0').collect())
+            .collect::<Vec<Vec<u8>>>();
+        let trailheads = (0..20)
+            .flat_map(|i| (0..20).map(move |j| (i, j)))
+            .filter(|(i, j)| topo[*i][*j] == 0)
+            .collect::<Vec<(usize, usize)>>();
+        assert!(trailheads.len() >= 8);
+    }
+
+    #[test]
+    fn test_day15() {
+        let input = day15(&mut Rng::new(3), 10, 6, 100);
+        let (grid, moves) = input.split_once("\n\n").unwrap();
+        assert!(grid.lines().next().unwrap().chars().all(|c| c == '#'));
+        assert_eq!(grid.matches('@').count(), 1);
+        assert_eq!(
+            moves.lines().map(|line| line.len()).collect::<Vec<usize>>(),
+            vec![70, 30]
+        );
+    }
+}
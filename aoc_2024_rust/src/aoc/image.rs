@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::hash::Hash;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+pub type Color = [u8; 3];
+
+pub const BLACK: Color = [0, 0, 0];
+pub const WHITE: Color = [255, 255, 255];
+pub const GREY: Color = [128, 128, 128];
+pub const RED: Color = [220, 50, 47];
+pub const GREEN: Color = [30, 160, 60];
+pub const BLUE: Color = [38, 110, 210];
+pub const YELLOW: Color = [240, 200, 40];
+
+/// Colour of each kind of grid cell, with a fallback for unlisted cells
+#[derive(Debug, Clone)]
+pub struct Palette<C> {
+    colors: HashMap<C, Color>,
+    default: Color,
+}
+
+impl<C: Eq + Hash> Palette<C> {
+    pub fn new(default: Color) -> Self {
+        Self {
+            colors: HashMap::new(),
+            default,
+        }
+    }
+
+    pub fn with(mut self, cell: C, color: Color) -> Self {
+        self.colors.insert(cell, color);
+        self
+    }
+
+    pub fn color_of(&self, cell: &C) -> Color {
+        *self.colors.get(cell).unwrap_or(&self.default)
+    }
+}
+
+/// An RGB image, where `(x, y)` is column `x` of row `y`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, background: Color) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![background; width * height],
+        }
+    }
+
+    /// One `scale` x `scale` block of pixels per cell, row `i` of the grid at `y == i`.
+    pub fn from_grid<C: Eq + Hash>(grid: &[Vec<C>], palette: &Palette<C>, scale: usize) -> Self {
+        let width = grid.iter().map(|row| row.len()).max().unwrap_or(0);
+        let mut image = Image::new(width * scale, grid.len() * scale, palette.default);
+        for (i, row) in grid.iter().enumerate() {
+            for (j, cell) in row.iter().enumerate() {
+                image.fill_cell((j, i), scale, palette.color_of(cell));
+            }
+        }
+
+        image
+    }
+
+    /// Points drawn in `color` on a `width` x `height` board of `scale` sized cells.
+    pub fn from_points(
+        (width, height): (usize, usize),
+        points: impl IntoIterator<Item = (usize, usize)>,
+        color: Color,
+        background: Color,
+        scale: usize,
+    ) -> Self {
+        let mut image = Image::new(width * scale, height * scale, background);
+        for point in points {
+            image.fill_cell(point, scale, color);
+        }
+
+        image
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Row by row, starting from the top left
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    pub fn get(&self, (x, y): (usize, usize)) -> Color {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Pixels outside of the image are ignored.
+    pub fn set(&mut self, (x, y): (usize, usize), color: Color) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = color;
+        }
+    }
+
+    fn fill_cell(&mut self, (x, y): (usize, usize), scale: usize, color: Color) {
+        for dy in 0..scale {
+            for dx in 0..scale {
+                self.set((x * scale + dx, y * scale + dy), color);
+            }
+        }
+    }
+
+    /// Binary PPM (P6)
+    pub fn write_ppm(&self, out: &mut impl Write) -> io::Result<()> {
+        write!(out, "P6\n{} {}\n255\n", self.width, self.height)?;
+        out.write_all(&self.pixels.concat())
+    }
+
+    pub fn write_png(&self, out: impl Write) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(out, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels.concat())?;
+        Ok(writer.finish()?)
+    }
+
+    /// Writes a PPM or PNG file, depending on the extension of `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|ext| ext.to_str());
+        if !matches!(extension, Some("ppm") | Some("png")) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unsupported image format: {}", path.display()),
+            ));
+        }
+
+        let mut out = BufWriter::new(File::create(path)?);
+        if extension == Some("ppm") {
+            self.write_ppm(&mut out)?;
+        } else {
+            self.write_png(&mut out)?;
+        }
+        out.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn grid_of(rows: &[&str]) -> Vec<Vec<char>> {
+        rows.iter().map(|row| row.chars().collect()).collect()
+    }
+
+    #[test]
+    fn test_from_grid() {
+        let grid = grid_of(&["#.", ".@"]);
+        let palette = Palette::new(WHITE).with('#', BLACK).with('@', RED);
+        let image = Image::from_grid(&grid, &palette, 2);
+        assert_eq!((image.width(), image.height()), (4, 4));
+        assert_eq!(image.get((0, 0)), BLACK);
+        assert_eq!(image.get((1, 1)), BLACK);
+        assert_eq!(image.get((2, 0)), WHITE);
+        assert_eq!(image.get((3, 3)), RED);
+    }
+
+    #[test]
+    fn test_from_points() {
+        let image = Image::from_points((3, 2), [(2, 1), (5, 5)], GREEN, BLACK, 1);
+        assert_eq!((image.width(), image.height()), (3, 2));
+        assert_eq!(image.get((2, 1)), GREEN);
+        assert_eq!(image.get((1, 1)), BLACK);
+    }
+
+    #[test]
+    fn test_write_ppm() {
+        let mut image = Image::new(2, 1, BLACK);
+        image.set((1, 0), [1, 2, 3]);
+        let mut out = Vec::new();
+        image.write_ppm(&mut out).unwrap();
+        assert_eq!(out, b"P6\n2 1\n255\n\x00\x00\x00\x01\x02\x03");
+    }
+
+    #[test]
+    fn test_write_png() {
+        let mut image = Image::new(3, 2, BLUE);
+        image.set((2, 1), YELLOW);
+        let mut out = Vec::new();
+        image.write_png(&mut out).unwrap();
+
+        let decoder = png::Decoder::new(out.as_slice());
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (3, 2));
+        assert_eq!(&pixels[..3], &BLUE);
+        assert_eq!(&pixels[15..18], &YELLOW);
+    }
+
+    #[test]
+    fn test_save_unsupported_format() {
+        let path = std::env::temp_dir().join("aoc_image_test.bmp");
+        let err = Image::new(1, 1, BLACK).save(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(!path.exists());
+    }
+}
@@ -1,7 +1,9 @@
 use std::env;
-use std::fs::File;
+use std::fmt;
+use std::fs::{self, File};
 use std::io::Read;
 use std::path::Path;
+use std::str::FromStr;
 
 pub fn read_input_for_day(day_number: u8) -> String {
     let input_dir = env::var("AOC_INPUT_DIR").unwrap();
@@ -14,3 +16,90 @@ pub fn read_input_for_day(day_number: u8) -> String {
         .unwrap();
     contents.trim().to_string()
 }
+
+/// The input for a day when `AOC_INPUT_DIR` is set and has one
+pub fn try_read_input_for_day(day_number: u8) -> Option<String> {
+    let input_dir = env::var("AOC_INPUT_DIR").ok()?;
+    let contents = fs::read_to_string(Path::new(&input_dir).join(format!("day{day_number}.txt")));
+    Some(contents.ok()?.trim().to_string())
+}
+
+/// Why an input couldn't be parsed, with the line it was found on (starting at 1)
+/// when there is one.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseError {
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            line: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn at_line(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line: Some(line),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {line}: {}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses `s` found on `line`, e.g. a number
+pub fn parse_at<T: FromStr>(s: &str, line: usize) -> Result<T, ParseError> {
+    s.parse::<T>()
+        .map_err(|_| ParseError::at_line(line, format!("invalid value {s:?}")))
+}
+
+/// Rows of characters, which must all be as long as the first one
+pub fn parse_grid(input: &str) -> Result<Vec<Vec<char>>, ParseError> {
+    let grid = input
+        .split('\n')
+        .map(|line| line.chars().collect::<Vec<char>>())
+        .collect::<Vec<Vec<char>>>();
+    let width = grid[0].len();
+    if width == 0 {
+        return Err(ParseError::at_line(1, "empty row"));
+    }
+    match grid.iter().position(|row| row.len() != width) {
+        Some(ix) => Err(ParseError::at_line(
+            ix + 1,
+            format!("expected {width} cells, found {}", grid[ix].len()),
+        )),
+        None => Ok(grid),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_at() {
+        assert_eq!(parse_at::<i32>("-12", 3), Ok(-12));
+        let err = parse_at::<u8>("300", 2).unwrap_err();
+        assert_eq!(err.to_string(), "line 2: invalid value \"300\"");
+    }
+
+    #[test]
+    fn test_parse_grid() {
+        assert_eq!(parse_grid("ab\ncd").unwrap()[1], vec!['c', 'd']);
+        let err = parse_grid("ab\nc\nde").unwrap_err();
+        assert_eq!(err, ParseError::at_line(2, "expected 2 cells, found 1"));
+        assert!(parse_grid("").is_err());
+    }
+}
@@ -0,0 +1,177 @@
+type Num = i64;
+type Digits = u64;
+
+/// Greatest common divisor, always non-negative.
+pub fn gcd(a: Num, b: Num) -> Num {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+
+    a
+}
+
+/// Least common multiple, or `None` if it doesn't fit in a `Num`.
+pub fn checked_lcm(a: Num, b: Num) -> Option<Num> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+
+    (a / gcd(a, b)).checked_mul(b).map(Num::abs)
+}
+
+pub fn lcm(a: Num, b: Num) -> Num {
+    checked_lcm(a, b).unwrap_or_else(|| panic!("lcm({a}, {b}) overflows"))
+}
+
+/// Returns `(g, x, y)` such that `a * x + b * y == g == gcd(a, b)`.
+pub fn extended_gcd(a: Num, b: Num) -> (Num, Num, Num) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// `(a * b) mod m` in `0..m`, without overflowing the intermediate product.
+pub fn mul_mod(a: Num, b: Num, m: Num) -> Num {
+    ((a as i128 * b as i128).rem_euclid(m as i128)) as Num
+}
+
+/// `x` in `0..m` such that `a * x == 1 (mod m)`, if `a` and `m` are coprime.
+pub fn mod_inverse(a: Num, m: Num) -> Option<Num> {
+    let (g, x, _) = extended_gcd(a.rem_euclid(m), m);
+    if g == 1 {
+        Some(x.rem_euclid(m))
+    } else {
+        None
+    }
+}
+
+/// Chinese Remainder Theorem over `(residue, modulus)` pairs.
+///
+/// Moduli don't need to be coprime. Returns the combined `(residue, modulus)`
+/// with the residue in `0..modulus`, or `None` if a modulus isn't positive,
+/// the congruences conflict or the combined modulus overflows.
+pub fn crt(congruences: &[(Num, Num)]) -> Option<(Num, Num)> {
+    let mut combined: (Num, Num) = (0, 1);
+    for (r2, m2) in congruences {
+        if *m2 <= 0 {
+            return None;
+        }
+        let (r1, m1) = combined;
+        let (g, p, _) = extended_gcd(m1, *m2);
+        let diff = r2 - r1;
+        if diff % g != 0 {
+            return None;
+        }
+        let m = checked_lcm(m1, *m2)?;
+        // r1 + m1 * k == r2 (mod m2)  =>  k == (diff / g) * p (mod m2 / g)
+        let k = mul_mod(diff / g, p, m2 / g);
+        let r = (r1 as i128 + m1 as i128 * k as i128).rem_euclid(m as i128) as Num;
+        combined = (r, m);
+    }
+
+    Some(combined)
+}
+
+/// Number of decimal digits in `n`, where `0` has one digit.
+pub fn count_digits(n: Digits) -> u32 {
+    n.checked_ilog10().unwrap_or(0) + 1
+}
+
+/// Splits `n` into its leading digits and its last `n_right` digits,
+/// e.g. `split_digits(1234, 2) == (12, 34)`.
+pub fn split_digits(n: Digits, n_right: u32) -> (Digits, Digits) {
+    match (10 as Digits).checked_pow(n_right) {
+        Some(pow) => (n / pow, n % pow),
+        None => (0, n),
+    }
+}
+
+/// Writes the digits of `b` after those of `a`, e.g. `12 || 345 == 12345`.
+pub fn checked_concat_digits(a: Digits, b: Digits) -> Option<Digits> {
+    (10 as Digits)
+        .checked_pow(count_digits(b))?
+        .checked_mul(a)?
+        .checked_add(b)
+}
+
+pub fn concat_digits(a: Digits, b: Digits) -> Digits {
+    checked_concat_digits(a, b).unwrap_or_else(|| panic!("{a} || {b} overflows"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_gcd_lcm() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(-12, 18), 6);
+        assert_eq!(gcd(0, 7), 7);
+        assert_eq!(lcm(4, 6), 12);
+        assert_eq!(lcm(101, 103), 10403);
+        assert_eq!(checked_lcm(Num::MAX, Num::MAX - 1), None);
+    }
+
+    #[test]
+    fn test_extended_gcd() {
+        for (a, b) in [(240, 46), (101, 103), (-35, 15), (0, 5)] {
+            let (g, x, y) = extended_gcd(a, b);
+            assert_eq!(g, gcd(a, b));
+            assert_eq!(a * x + b * y, g);
+        }
+    }
+
+    #[test]
+    fn test_mod_inverse() {
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(101, 103), Some(51));
+        assert_eq!(mod_inverse(6, 9), None);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(50, 101), (60, 103)]).unwrap().0 % 101, 50);
+        assert_eq!(crt(&[(50, 101), (60, 103)]).unwrap().0 % 103, 60);
+        // Non-coprime moduli
+        assert_eq!(crt(&[(2, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[]), Some((0, 1)));
+        // Moduli must be positive
+        assert_eq!(crt(&[(0, 0)]), None);
+        assert_eq!(crt(&[(2, 3), (1, -5)]), None);
+    }
+
+    #[test]
+    fn test_digits() {
+        assert_eq!(count_digits(0), 1);
+        assert_eq!(count_digits(9), 1);
+        assert_eq!(count_digits(10), 2);
+        assert_eq!(count_digits(u64::MAX), 20);
+        assert_eq!(split_digits(1234, 2), (12, 34));
+        assert_eq!(split_digits(1000, 2), (10, 0));
+        assert_eq!(split_digits(5, 30), (0, 5));
+    }
+
+    #[test]
+    fn test_concat_digits() {
+        assert_eq!(concat_digits(12, 345), 12345);
+        assert_eq!(concat_digits(15, 6), 156);
+        assert_eq!(concat_digits(1, 0), 10);
+        assert_eq!(checked_concat_digits(u64::MAX, 1), None);
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Hash-map cache for pure recursive functions, keyed by their arguments.
+///
+/// The function takes the `Memo` as an argument and computes each result through
+/// `get_or_compute`, passing the memo on to its recursive calls:
+///
+/// ```ignore
+/// fn fib(n: u64, memo: &mut Memo<u64, u64>) -> u64 {
+///     memo.get_or_compute(n, |memo| {
+///         if n < 2 { n } else { fib(n - 1, memo) + fib(n - 2, memo) }
+///     })
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+    hits: usize,
+    misses: usize,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct MemoStats {
+    pub size: usize,
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl<K: Eq + Hash, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Self {
+            cache: HashMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Returns the cached value for `key`, or computes and caches it.
+    pub fn get_or_compute(&mut self, key: K, compute: impl FnOnce(&mut Self) -> V) -> V {
+        if let Some(value) = self.cache.get(&key) {
+            self.hits += 1;
+            return value.clone();
+        }
+
+        self.misses += 1;
+        let value = compute(self);
+        self.cache.insert(key, value.clone());
+        value
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        MemoStats {
+            size: self.cache.len(),
+            hits: self.hits,
+            misses: self.misses,
+        }
+    }
+
+    /// Empties the cache and resets the statistics.
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.hits = 0;
+        self.misses = 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fib(n: u64, memo: &mut Memo<u64, u64>) -> u64 {
+        memo.get_or_compute(n, |memo| {
+            if n < 2 {
+                n
+            } else {
+                fib(n - 1, memo) + fib(n - 2, memo)
+            }
+        })
+    }
+
+    #[test]
+    fn test_memo() {
+        let mut memo = Memo::new();
+        assert_eq!(fib(90, &mut memo), 2880067194370816120);
+        assert_eq!(
+            memo.stats(),
+            MemoStats {
+                size: 91,
+                hits: 88,
+                misses: 91
+            }
+        );
+
+        assert_eq!(fib(90, &mut memo), 2880067194370816120);
+        assert_eq!(memo.stats().hits, 89);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut memo = Memo::new();
+        fib(10, &mut memo);
+        memo.clear();
+        assert_eq!(
+            memo.stats(),
+            MemoStats {
+                size: 0,
+                hits: 0,
+                misses: 0
+            }
+        );
+        assert_eq!(fib(10, &mut memo), 55);
+    }
+}
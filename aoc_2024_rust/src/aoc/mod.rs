@@ -1,3 +1,4 @@
+pub mod cycle;
 pub mod day1;
 pub mod day10;
 pub mod day11;
@@ -10,7 +11,19 @@ pub mod day5;
 pub mod day6;
 pub mod day7;
 pub mod day8;
+pub mod gen;
+pub mod image;
 pub mod input;
+pub mod math;
+pub mod memo;
+pub mod options;
+pub mod order;
+pub mod overlay;
+pub mod ranges;
+pub mod recorder;
+#[cfg(test)]
+pub mod snapshot;
+pub mod viewer;
 
 static DAYS: &[fn()] = &[
     day1::run,
@@ -42,3 +55,38 @@ pub fn run_all() {
 pub fn run(day: usize) {
     DAYS[day - 1]()
 }
+
+pub fn run_with_options(day: usize, options: &options::Options) {
+    match day {
+        1 => day1::run_with(options),
+        2 => day2::run_with(options),
+        3 => day3::run_with(options),
+        6 => day6::run_with(options),
+        8 => day8::run_with(options),
+        10 => day10::run_with(options),
+        14 => day14::run_with(options),
+        15 => day15::run_with(options),
+        _ => panic!("Day {day} doesn't take any options"),
+    }
+}
+
+/// Both answers for `day`, or why `input` can't be solved. Never panics on
+/// bad input, which makes it the entry point for the fuzz targets.
+pub fn try_solve(day: usize, input: &str) -> Result<(String, String), input::ParseError> {
+    let input = input.to_string();
+    match day {
+        1 => day1::try_solve(&input),
+        2 => day2::try_solve(&input),
+        3 => day3::try_solve(&input),
+        4 => day4::try_solve(&input),
+        5 => day5::try_solve(&input),
+        6 => day6::try_solve(&input),
+        7 => day7::try_solve(&input),
+        8 => day8::try_solve(&input),
+        10 => day10::try_solve(&input),
+        11 => day11::try_solve(&input),
+        14 => day14::try_solve(&input),
+        15 => day15::try_solve(&input),
+        _ => Err(input::ParseError::new(format!("Day {day} isn't solved"))),
+    }
+}
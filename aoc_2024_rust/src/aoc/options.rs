@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Command line options of the form `--name=value` or `--flag`
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Options {
+    values: HashMap<String, String>,
+}
+
+impl Options {
+    pub fn parse(args: &[String]) -> Self {
+        let mut values = HashMap::new();
+        for arg in args {
+            let Some(option) = arg.strip_prefix("--") else {
+                panic!("Unexpected argument: {arg}");
+            };
+            match option.split_once('=') {
+                Some((name, value)) => values.insert(name.to_string(), value.to_string()),
+                None => values.insert(option.to_string(), String::new()),
+            };
+        }
+
+        Self { values }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.values.contains_key(name)
+    }
+
+    /// A switch given as `--name` or `--name=true|false`, off when missing
+    pub fn switch(&self, name: &str) -> bool {
+        match self.get(name) {
+            Some("") => true,
+            Some(_) => self.parsed(name).unwrap(),
+            None => false,
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(|value| value.as_str())
+    }
+
+    pub fn parsed<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.get(name).map(|value| {
+            value
+                .parse::<T>()
+                .unwrap_or_else(|_| panic!("Invalid value for --{name}: {value}"))
+        })
+    }
+
+    /// The puzzle part from `--part=1|2`, or `default` without it
+    pub fn part(&self, default: u8) -> u8 {
+        match self.parsed::<u8>("part").unwrap_or(default) {
+            part @ (1 | 2) => part,
+            part => panic!("Invalid value for --part: {part}, expected 1 or 2"),
+        }
+    }
+
+    /// Comma separated values, e.g. `--ticks=1,2,3`
+    pub fn list<T: FromStr>(&self, name: &str) -> Vec<T> {
+        self.get(name)
+            .map(|values| {
+                values
+                    .split(',')
+                    .map(|value| {
+                        value
+                            .parse::<T>()
+                            .unwrap_or_else(|_| panic!("Invalid value for --{name}: {value}"))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let options = Options::parse(&args(&["--csv", "--out-dir=images", "--ticks=1,20"]));
+        assert!(options.flag("csv"));
+        assert!(!options.flag("explain"));
+        assert_eq!(options.get("out-dir"), Some("images"));
+        assert_eq!(options.list::<u32>("ticks"), vec![1, 20]);
+        assert_eq!(options.list::<u32>("missing"), vec![]);
+        assert_eq!(options.parsed::<u32>("missing"), None);
+    }
+
+    #[test]
+    fn test_switch() {
+        let options = Options::parse(&args(&["--on", "--yes=true", "--no=false"]));
+        assert!(options.switch("on"));
+        assert!(options.switch("yes"));
+        assert!(!options.switch("no"));
+        assert!(!options.switch("missing"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid value for --on: 1")]
+    fn test_switch_invalid_value() {
+        Options::parse(&args(&["--on=1"])).switch("on");
+    }
+
+    #[test]
+    fn test_part() {
+        assert_eq!(Options::default().part(1), 1);
+        assert_eq!(Options::default().part(2), 2);
+        assert_eq!(Options::parse(&args(&["--part=2"])).part(1), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid value for --part: 3, expected 1 or 2")]
+    fn test_part_invalid_value() {
+        Options::parse(&args(&["--part=3"])).part(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid value for --ticks: x")]
+    fn test_parse_invalid_value() {
+        let options = Options::parse(&args(&["--ticks=1,x"]));
+        options.list::<u32>("ticks");
+    }
+}
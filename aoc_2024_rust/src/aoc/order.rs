@@ -0,0 +1,229 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::fmt;
+use std::hash::Hash;
+
+/// Rules of the form "`a` must come before `b`", stored as a directed graph.
+#[derive(Debug, Clone, Default)]
+pub struct PrecedenceGraph<T> {
+    successors: HashMap<T, Vec<T>>,
+}
+
+/// Returned when the rules restricted to a set of nodes contain a cycle.
+/// The cycle is listed in rule order and starts and ends with the same node.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CycleError<T> {
+    pub cycle: Vec<T>,
+}
+
+impl<T: fmt::Display> fmt::Display for CycleError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cycle = self
+            .cycle
+            .iter()
+            .map(|node| node.to_string())
+            .collect::<Vec<String>>()
+            .join(" -> ");
+        write!(f, "ordering rules contain a cycle: {cycle}")
+    }
+}
+
+impl<T: fmt::Debug + fmt::Display> std::error::Error for CycleError<T> {}
+
+impl<T: Copy + Eq + Hash> PrecedenceGraph<T> {
+    pub fn new() -> Self {
+        Self {
+            successors: HashMap::new(),
+        }
+    }
+
+    pub fn from_rules(rules: &[(T, T)]) -> Self {
+        let mut graph = Self::new();
+        for (before, after) in rules {
+            graph.add_rule(*before, *after);
+        }
+
+        graph
+    }
+
+    pub fn add_rule(&mut self, before: T, after: T) {
+        let successors = self.successors.entry(before).or_default();
+        if !successors.contains(&after) {
+            successors.push(after);
+        }
+    }
+
+    /// Nodes that `node` must come before, in the order the rules were added.
+    pub fn successors_of(&self, node: &T) -> &[T] {
+        self.successors.get(node).map_or(&[], |s| s.as_slice())
+    }
+
+    /// True if there is a rule saying `a` must come before `b`.
+    pub fn precedes(&self, a: &T, b: &T) -> bool {
+        self.successors_of(a).contains(b)
+    }
+
+    /// True if no rule between members of `sequence` is broken by its order.
+    pub fn is_consistent(&self, sequence: &[T]) -> bool {
+        let mut seen = HashSet::<T>::new();
+        for node in sequence {
+            if self.successors_of(node).iter().any(|s| seen.contains(s)) {
+                return false;
+            }
+            seen.insert(*node);
+        }
+
+        true
+    }
+
+    /// Kahn's topological sort using only the rules between members of `nodes`.
+    /// Whenever several nodes are free to go next, the one first in `nodes`
+    /// goes, so nodes that already follow the rules come back in input order.
+    /// Repeated nodes appear once, as if only their first copy was given.
+    pub fn sort_subset(&self, nodes: &[T]) -> Result<Vec<T>, CycleError<T>> {
+        let mut members = HashSet::<T>::new();
+        let unique = nodes
+            .iter()
+            .copied()
+            .filter(|node| members.insert(*node))
+            .collect::<Vec<T>>();
+        let index_of = unique
+            .iter()
+            .enumerate()
+            .map(|(ix, node)| (*node, ix))
+            .collect::<HashMap<T, usize>>();
+        let mut in_degree = HashMap::<T, usize>::new();
+        for node in unique.iter() {
+            in_degree.entry(*node).or_insert(0);
+            for succ in self.successors_of(node) {
+                if members.contains(succ) {
+                    *in_degree.entry(*succ).or_insert(0) += 1;
+                }
+            }
+        }
+
+        // Input positions of the nodes with no predecessors left, earliest first
+        let mut ready = unique
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| in_degree[node] == 0)
+            .map(|(ix, _)| Reverse(ix))
+            .collect::<BinaryHeap<Reverse<usize>>>();
+
+        let mut sorted = Vec::<T>::with_capacity(members.len());
+        while let Some(Reverse(ix)) = ready.pop() {
+            let node = unique[ix];
+            sorted.push(node);
+            for succ in self.successors_of(&node) {
+                if let Some(degree) = in_degree.get_mut(succ) {
+                    *degree -= 1;
+                    if *degree == 0 {
+                        ready.push(Reverse(index_of[succ]));
+                    }
+                }
+            }
+        }
+
+        if sorted.len() == members.len() {
+            Ok(sorted)
+        } else {
+            Err(CycleError {
+                cycle: self.cycle_among(&in_degree),
+            })
+        }
+    }
+
+    /// Every node left with a positive in-degree after Kahn's algorithm stalls
+    /// has a predecessor that is also left, so walking predecessors must loop.
+    fn cycle_among(&self, in_degree: &HashMap<T, usize>) -> Vec<T> {
+        let remaining = in_degree
+            .iter()
+            .filter(|(_, degree)| **degree > 0)
+            .map(|(node, _)| *node)
+            .collect::<HashSet<T>>();
+        let predecessor_of = |node: &T| {
+            *remaining
+                .iter()
+                .find(|pred| self.precedes(pred, node))
+                .expect("stalled node has a remaining predecessor")
+        };
+
+        let mut node = *remaining.iter().next().expect("cycle is not empty");
+        let mut walked = Vec::<T>::new();
+        while !walked.contains(&node) {
+            walked.push(node);
+            node = predecessor_of(&node);
+        }
+
+        let start = walked.iter().position(|n| *n == node).unwrap();
+        let mut cycle = walked[start..].to_vec();
+        cycle.push(node);
+        cycle.reverse();
+        cycle
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_precedes() {
+        let graph = PrecedenceGraph::from_rules(&[(1, 2), (2, 3)]);
+        assert!(graph.precedes(&1, &2));
+        assert!(!graph.precedes(&2, &1));
+        assert!(!graph.precedes(&1, &3));
+        assert_eq!(graph.successors_of(&4), &[] as &[i32]);
+    }
+
+    #[test]
+    fn test_is_consistent() {
+        let graph = PrecedenceGraph::from_rules(&[(1, 2), (2, 3), (5, 1)]);
+        assert!(graph.is_consistent(&[1, 2, 3]));
+        assert!(graph.is_consistent(&[5, 4, 3]));
+        assert!(!graph.is_consistent(&[1, 3, 2]));
+        assert!(!graph.is_consistent(&[1, 5]));
+    }
+
+    #[test]
+    fn test_sort_subset() {
+        let graph = PrecedenceGraph::from_rules(&[(1, 2), (2, 3), (3, 4)]);
+        assert_eq!(graph.sort_subset(&[4, 2, 3]), Ok(vec![2, 3, 4]));
+        // Nodes already in rule order come back unchanged
+        assert_eq!(graph.sort_subset(&[9, 2, 7, 4]), Ok(vec![9, 2, 7, 4]));
+        // Otherwise the earliest node that is free to go goes first
+        assert_eq!(graph.sort_subset(&[3, 9, 1, 2]), Ok(vec![9, 1, 2, 3]));
+        assert_eq!(graph.sort_subset(&[4, 8, 3, 7]), Ok(vec![8, 3, 4, 7]));
+    }
+
+    #[test]
+    fn test_sort_subset_duplicates() {
+        let graph = PrecedenceGraph::from_rules(&[(1, 2)]);
+        assert_eq!(graph.sort_subset(&[1, 2, 1]), Ok(vec![1, 2]));
+        assert_eq!(graph.sort_subset(&[2, 2, 5, 1]), Ok(vec![5, 1, 2]));
+    }
+
+    #[test]
+    fn test_sort_subset_ignores_cycles_outside_subset() {
+        // 1 -> 2 -> 3 -> 1 is a cycle, but not once 3 is left out
+        let graph = PrecedenceGraph::from_rules(&[(1, 2), (2, 3), (3, 1)]);
+        assert_eq!(graph.sort_subset(&[2, 1]), Ok(vec![1, 2]));
+    }
+
+    #[test]
+    fn test_sort_subset_cycle() {
+        let graph = PrecedenceGraph::from_rules(&[(1, 2), (2, 3), (3, 1), (0, 1)]);
+        let err = graph.sort_subset(&[0, 1, 2, 3]).unwrap_err();
+        assert_eq!(err.cycle.len(), 4);
+        assert_eq!(err.cycle[0], err.cycle[3]);
+        for pair in err.cycle.windows(2) {
+            assert!(graph.precedes(&pair[0], &pair[1]));
+        }
+        assert!(err
+            .to_string()
+            .starts_with("ordering rules contain a cycle: "));
+        // Repeated nodes don't hide or add to the cycle
+        let err = graph.sort_subset(&[1, 2, 3, 1, 2]).unwrap_err();
+        assert_eq!(err.cycle.len(), 4);
+    }
+}
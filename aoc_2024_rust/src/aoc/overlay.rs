@@ -0,0 +1,262 @@
+use crate::aoc::image::Color;
+use crate::aoc::options::Options;
+use std::cmp::Ordering::{Equal, Greater, Less};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Row and column of a grid cell
+pub type Cell = (usize, usize);
+
+#[derive(Debug, Clone)]
+enum Layer {
+    /// Points keep the grid's glyph unless one is given
+    Points {
+        points: Vec<Cell>,
+        glyph: Option<char>,
+        color: Color,
+    },
+    /// Each cell of the path points to the next one
+    Path { path: Vec<Cell>, color: Color },
+}
+
+/// A grid of characters with layers drawn on top, later layers over earlier ones
+#[derive(Debug, Clone)]
+pub struct Overlay {
+    grid: Vec<Vec<char>>,
+    layers: Vec<Layer>,
+}
+
+fn arrow(from: Cell, to: Cell) -> char {
+    match (to.0.cmp(&from.0), to.1.cmp(&from.1)) {
+        (Less, Less) => '↖',
+        (Less, Equal) => '↑',
+        (Less, Greater) => '↗',
+        (Equal, Less) => '←',
+        (Equal, Greater) => '→',
+        (Greater, Less) => '↙',
+        (Greater, Equal) => '↓',
+        (Greater, Greater) => '↘',
+        (Equal, Equal) => '•',
+    }
+}
+
+impl Overlay {
+    pub fn new(grid: &[Vec<char>]) -> Self {
+        Self {
+            grid: grid.to_vec(),
+            layers: Vec::new(),
+        }
+    }
+
+    pub fn points(
+        mut self,
+        points: impl IntoIterator<Item = Cell>,
+        glyph: Option<char>,
+        color: Color,
+    ) -> Self {
+        self.layers.push(Layer::Points {
+            points: points.into_iter().collect(),
+            glyph,
+            color,
+        });
+        self
+    }
+
+    pub fn path(mut self, path: impl IntoIterator<Item = Cell>, color: Color) -> Self {
+        self.layers.push(Layer::Path {
+            path: path.into_iter().collect(),
+            color,
+        });
+        self
+    }
+
+    /// The grid with every layer applied, and the colour of each overlaid cell.
+    /// Cells outside of the grid are ignored.
+    pub fn cells(&self) -> Vec<Vec<(char, Option<Color>)>> {
+        let mut cells = self
+            .grid
+            .iter()
+            .map(|row| row.iter().map(|c| (*c, None)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let mut draw = |(i, j): Cell, glyph: char, color: Color| {
+            if let Some(cell) = cells.get_mut(i).and_then(|row| row.get_mut(j)) {
+                *cell = (glyph, Some(color));
+            }
+        };
+
+        for layer in self.layers.iter() {
+            match layer {
+                Layer::Points {
+                    points,
+                    glyph,
+                    color,
+                } => {
+                    for point in points {
+                        let glyph = glyph
+                            .or_else(|| self.grid.get(point.0)?.get(point.1).copied())
+                            .unwrap_or(' ');
+                        draw(*point, glyph, *color);
+                    }
+                }
+                Layer::Path { path, color } => {
+                    // Steps that stay in place (e.g. turning) keep the previous arrow
+                    let mut last = '•';
+                    for (from, to) in path.iter().zip(path.iter().skip(1)) {
+                        if from != to {
+                            last = arrow(*from, *to);
+                        }
+                        draw(*from, last, *color);
+                    }
+                    if let Some(end) = path.last() {
+                        draw(*end, last, *color);
+                    }
+                }
+            }
+        }
+
+        cells
+    }
+
+    /// Overlaid cells in 24-bit ANSI colours
+    pub fn render_ansi(&self) -> String {
+        self.cells()
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|(c, color)| match color {
+                        Some([r, g, b]) => format!("\x1b[1;38;2;{r};{g};{b}m{c}\x1b[0m"),
+                        None => c.to_string(),
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// One `cell_size` pixel square per cell, overlaid cells on a tinted background
+    pub fn render_svg(&self, cell_size: usize) -> String {
+        let cells = self.cells();
+        let width = cells.iter().map(|row| row.len()).max().unwrap_or(0) * cell_size;
+        let height = cells.len() * cell_size;
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+             font-family=\"monospace\" font-size=\"{}\" text-anchor=\"middle\" \
+             dominant-baseline=\"central\">\n\
+             <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n",
+            cell_size * 3 / 4
+        );
+        for (i, row) in cells.iter().enumerate() {
+            for (j, (c, color)) in row.iter().enumerate() {
+                let (x, y) = (j * cell_size, i * cell_size);
+                let fill = match color {
+                    Some([r, g, b]) => {
+                        svg.push_str(&format!(
+                            "<rect x=\"{x}\" y=\"{y}\" width=\"{cell_size}\" height=\"{cell_size}\" \
+                             fill=\"rgb({r},{g},{b})\" fill-opacity=\"0.25\"/>\n"
+                        ));
+                        format!("rgb({r},{g},{b})")
+                    }
+                    None => "grey".to_string(),
+                };
+                if *c != ' ' && *c != '.' {
+                    let text = match c {
+                        '<' => "&lt;".to_string(),
+                        '>' => "&gt;".to_string(),
+                        '&' => "&amp;".to_string(),
+                        c => c.to_string(),
+                    };
+                    svg.push_str(&format!(
+                        "<text x=\"{}\" y=\"{}\" fill=\"{fill}\">{text}</text>\n",
+                        x + cell_size / 2,
+                        y + cell_size / 2
+                    ));
+                }
+            }
+        }
+        svg.push_str("</svg>\n");
+
+        svg
+    }
+
+    pub fn save_svg(&self, path: impl AsRef<Path>, cell_size: usize) -> io::Result<()> {
+        fs::write(path, self.render_svg(cell_size))
+    }
+}
+
+/// With `--overlay` prints the overlay from `overlay` as ANSI text, with
+/// `--overlay=<path>` saves it as an SVG with `--cell-size` (default 16) pixel cells.
+pub fn show_overlay_if_requested(options: &Options, overlay: impl FnOnce() -> Overlay) {
+    let Some(path) = options.get("overlay") else {
+        return;
+    };
+    let overlay = overlay();
+    if path.is_empty() {
+        println!("{}", overlay.render_ansi());
+    } else {
+        overlay
+            .save_svg(path, options.parsed("cell-size").unwrap_or(16))
+            .unwrap_or_else(|err| panic!("Couldn't save the overlay to {path}: {err}"));
+        println!("\tSaved overlay to {path}");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::aoc::image::{BLUE, RED};
+
+    fn grid_of(rows: &[&str]) -> Vec<Vec<char>> {
+        rows.iter().map(|row| row.chars().collect()).collect()
+    }
+
+    fn glyphs(overlay: &Overlay) -> Vec<String> {
+        overlay
+            .cells()
+            .iter()
+            .map(|row| row.iter().map(|(c, _)| c).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_points() {
+        let overlay = Overlay::new(&grid_of(&["a..", "..#"]))
+            .points([(0, 0), (1, 1), (5, 5)], None, RED)
+            .points([(1, 1)], Some('O'), BLUE);
+        assert_eq!(glyphs(&overlay), vec!["a..", ".O#"]);
+        let cells = overlay.cells();
+        assert_eq!(cells[0][0], ('a', Some(RED)));
+        assert_eq!(cells[1][1], ('O', Some(BLUE)));
+        assert_eq!(cells[1][2], ('#', None));
+    }
+
+    #[test]
+    fn test_path() {
+        // Turning in place at (0, 2) keeps the arrow
+        let path = [(0, 0), (0, 1), (0, 2), (0, 2), (1, 2), (2, 2), (2, 1)];
+        let overlay = Overlay::new(&grid_of(&["...", "...", "..."])).path(path, BLUE);
+        assert_eq!(glyphs(&overlay), vec!["→→↓", "..↓", ".←←"]);
+    }
+
+    #[test]
+    fn test_render_ansi() {
+        let overlay = Overlay::new(&grid_of(&["#.", ".."])).points([(1, 0)], Some('X'), RED);
+        assert_eq!(overlay.render_ansi(), "#.\n\x1b[1;38;2;220;50;47mX\x1b[0m.");
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let overlay = Overlay::new(&grid_of(&["<.", ".."])).points([(1, 1)], Some('O'), RED);
+        let svg = overlay.render_svg(10);
+        assert!(
+            svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"20\" height=\"20\"")
+        );
+        assert!(svg.contains("<text x=\"5\" y=\"5\" fill=\"grey\">&lt;</text>"));
+        assert!(svg.contains(
+            "<rect x=\"10\" y=\"10\" width=\"10\" height=\"10\" fill=\"rgb(220,50,47)\""
+        ));
+        assert!(svg.contains("<text x=\"15\" y=\"15\" fill=\"rgb(220,50,47)\">O</text>"));
+        assert_eq!(svg.matches("<text").count(), 2);
+        assert!(svg.ends_with("</svg>\n"));
+    }
+}
@@ -0,0 +1,429 @@
+use std::ops::{Add, Mul, Range, Sub};
+
+/// Numbers that ranges can be built from
+pub trait Bound: Copy + Ord + Default + Add<Output = Self> + Sub<Output = Self> {}
+impl<T: Copy + Ord + Default + Add<Output = T> + Sub<Output = T>> Bound for T {}
+
+fn is_empty<T: Bound>(range: &Range<T>) -> bool {
+    range.start >= range.end
+}
+
+fn intersect<T: Bound>(a: &Range<T>, b: &Range<T>) -> Range<T> {
+    a.start.max(b.start)..a.end.min(b.end)
+}
+
+/// A set of values stored as sorted, disjoint, non-touching half-open ranges.
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct RangeSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T: Bound> RangeSet<T> {
+    pub fn new() -> Self {
+        Self { ranges: Vec::new() }
+    }
+
+    pub fn ranges(&self) -> &[Range<T>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Adds `range`, merging it with any ranges it overlaps or touches.
+    pub fn insert(&mut self, range: Range<T>) {
+        if is_empty(&range) {
+            return;
+        }
+
+        let first = self.ranges.partition_point(|r| r.end < range.start);
+        let last = self.ranges.partition_point(|r| r.start <= range.end);
+        let mut merged = range;
+        if first < last {
+            merged.start = merged.start.min(self.ranges[first].start);
+            merged.end = merged.end.max(self.ranges[last - 1].end);
+        }
+        self.ranges.splice(first..last, [merged]);
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let ix = self.ranges.partition_point(|r| r.end <= *value);
+        ix < self.ranges.len() && self.ranges[ix].start <= *value
+    }
+
+    /// True if every value of `range` is in the set.
+    pub fn contains_range(&self, range: &Range<T>) -> bool {
+        if is_empty(range) {
+            return true;
+        }
+        let ix = self.ranges.partition_point(|r| r.end <= range.start);
+        ix < self.ranges.len()
+            && self.ranges[ix].start <= range.start
+            && range.end <= self.ranges[ix].end
+    }
+
+    /// Total number of values in the set.
+    pub fn len(&self) -> T {
+        self.ranges
+            .iter()
+            .fold(T::default(), |acc, r| acc + (r.end - r.start))
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut set = self.clone();
+        for range in other.ranges.iter() {
+            set.insert(range.clone());
+        }
+
+        set
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let overlap = intersect(&self.ranges[i], &other.ranges[j]);
+            if !is_empty(&overlap) {
+                ranges.push(overlap);
+            }
+            if self.ranges[i].end < other.ranges[j].end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        Self { ranges }
+    }
+
+    /// Values in `self` that aren't in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let mut j = 0;
+        for range in self.ranges.iter() {
+            let mut start = range.start;
+            while j < other.ranges.len() && other.ranges[j].end <= start {
+                j += 1;
+            }
+            let mut k = j;
+            while k < other.ranges.len() && other.ranges[k].start < range.end {
+                if start < other.ranges[k].start {
+                    ranges.push(start..other.ranges[k].start);
+                }
+                start = start.max(other.ranges[k].end);
+                k += 1;
+            }
+            if start < range.end {
+                ranges.push(start..range.end);
+            }
+        }
+
+        Self { ranges }
+    }
+}
+
+impl<T: Bound> FromIterator<Range<T>> for RangeSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for range in iter {
+            set.insert(range);
+        }
+
+        set
+    }
+}
+
+/// An axis-aligned rectangle of the half-open ranges `x` and `y`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Rect<T> {
+    pub x: Range<T>,
+    pub y: Range<T>,
+}
+
+impl<T: Bound> Rect<T> {
+    pub fn new(x: Range<T>, y: Range<T>) -> Self {
+        Self { x, y }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        is_empty(&self.x) || is_empty(&self.y)
+    }
+
+    pub fn contains(&self, (x, y): (T, T)) -> bool {
+        self.x.contains(&x) && self.y.contains(&y)
+    }
+
+    pub fn contains_rect(&self, other: &Rect<T>) -> bool {
+        other.is_empty()
+            || (self.x.start <= other.x.start
+                && other.x.end <= self.x.end
+                && self.y.start <= other.y.start
+                && other.y.end <= self.y.end)
+    }
+
+    pub fn area(&self) -> T
+    where
+        T: Mul<Output = T>,
+    {
+        if self.is_empty() {
+            T::default()
+        } else {
+            (self.x.end - self.x.start) * (self.y.end - self.y.start)
+        }
+    }
+
+    pub fn intersection(&self, other: &Rect<T>) -> Option<Rect<T>> {
+        let rect = Rect::new(intersect(&self.x, &other.x), intersect(&self.y, &other.y));
+        if rect.is_empty() {
+            None
+        } else {
+            Some(rect)
+        }
+    }
+
+    /// The parts of `self` outside of `other`, as up to four disjoint rects.
+    pub fn subtract(&self, other: &Rect<T>) -> Vec<Rect<T>> {
+        let Some(overlap) = self.intersection(other) else {
+            return if self.is_empty() {
+                vec![]
+            } else {
+                vec![self.clone()]
+            };
+        };
+
+        // Full-width bands above and below the overlap, then the sides of it
+        [
+            Rect::new(self.x.clone(), self.y.start..overlap.y.start),
+            Rect::new(self.x.clone(), overlap.y.end..self.y.end),
+            Rect::new(self.x.start..overlap.x.start, overlap.y.clone()),
+            Rect::new(overlap.x.end..self.x.end, overlap.y.clone()),
+        ]
+        .into_iter()
+        .filter(|rect| !rect.is_empty())
+        .collect()
+    }
+
+    /// Combines two rects into one if together they form a rectangle.
+    pub fn merge(&self, other: &Rect<T>) -> Option<Rect<T>> {
+        if self.x == other.x && (self.y.end == other.y.start || other.y.end == self.y.start) {
+            Some(Rect::new(
+                self.x.clone(),
+                self.y.start.min(other.y.start)..self.y.end.max(other.y.end),
+            ))
+        } else if self.y == other.y && (self.x.end == other.x.start || other.x.end == self.x.start)
+        {
+            Some(Rect::new(
+                self.x.start.min(other.x.start)..self.x.end.max(other.x.end),
+                self.y.clone(),
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+/// A set of cells stored as disjoint rectangles.
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct RectSet<T> {
+    rects: Vec<Rect<T>>,
+}
+
+impl<T: Bound> RectSet<T> {
+    pub fn new() -> Self {
+        Self { rects: Vec::new() }
+    }
+
+    pub fn rects(&self) -> &[Rect<T>] {
+        &self.rects
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    pub fn insert(&mut self, rect: Rect<T>) {
+        let mut pieces = vec![rect];
+        for existing in self.rects.iter() {
+            pieces = pieces
+                .iter()
+                .flat_map(|piece| piece.subtract(existing))
+                .collect();
+        }
+        self.rects.extend(pieces);
+        self.merge();
+    }
+
+    pub fn contains(&self, point: (T, T)) -> bool {
+        self.rects.iter().any(|rect| rect.contains(point))
+    }
+
+    pub fn contains_rect(&self, rect: &Rect<T>) -> bool {
+        RectSet::from_iter([rect.clone()])
+            .difference(self)
+            .is_empty()
+    }
+
+    /// Total number of cells in the set.
+    pub fn area(&self) -> T
+    where
+        T: Mul<Output = T>,
+    {
+        self.rects
+            .iter()
+            .fold(T::default(), |acc, rect| acc + rect.area())
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut set = self.clone();
+        for rect in other.rects.iter() {
+            set.insert(rect.clone());
+        }
+
+        set
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut set = Self::new();
+        for a in self.rects.iter() {
+            for b in other.rects.iter() {
+                if let Some(rect) = a.intersection(b) {
+                    set.rects.push(rect);
+                }
+            }
+        }
+        set.merge();
+
+        set
+    }
+
+    /// Cells in `self` that aren't in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut rects = self.rects.clone();
+        for b in other.rects.iter() {
+            rects = rects.iter().flat_map(|a| a.subtract(b)).collect();
+        }
+        let mut set = Self { rects };
+        set.merge();
+
+        set
+    }
+
+    /// Combines neighboring rects that together form a rectangle.
+    fn merge(&mut self) {
+        let mut merged = true;
+        while merged {
+            merged = false;
+            'search: for i in 0..self.rects.len() {
+                for j in i + 1..self.rects.len() {
+                    if let Some(rect) = self.rects[i].merge(&self.rects[j]) {
+                        self.rects[i] = rect;
+                        self.rects.swap_remove(j);
+                        merged = true;
+                        break 'search;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<T: Bound> FromIterator<Rect<T>> for RectSet<T> {
+    fn from_iter<I: IntoIterator<Item = Rect<T>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for rect in iter {
+            set.insert(rect);
+        }
+
+        set
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_range_set_insert() {
+        let set = RangeSet::from_iter([5..8, 0..2, 2..3, 7..10, 12..12]);
+        assert_eq!(set.ranges(), &[0..3, 5..10]);
+        assert_eq!(set.len(), 8);
+
+        let set = RangeSet::from_iter([0..2, 4..6, 8..10, 1..9]);
+        assert_eq!(set.ranges(), &[0..10]);
+    }
+
+    #[test]
+    fn test_range_set_contains() {
+        let set = RangeSet::from_iter([0..3, 5..10]);
+        assert!(set.contains(&0));
+        assert!(set.contains(&9));
+        assert!(!set.contains(&3));
+        assert!(!set.contains(&-1));
+        assert!(set.contains_range(&(6..10)));
+        assert!(!set.contains_range(&(2..6)));
+        assert!(set.contains_range(&(20..20)));
+    }
+
+    #[test]
+    fn test_range_set_operations() {
+        let a = RangeSet::from_iter([0..5, 10..15]);
+        let b = RangeSet::from_iter([3..12, 14..20]);
+        assert_eq!(a.union(&b).len(), 20);
+        assert_eq!(a.union(&b).ranges().len(), 1);
+        assert_eq!(a.intersection(&b).ranges(), &[3..5, 10..12, 14..15]);
+        assert_eq!(a.difference(&b).ranges(), &[0..3, 12..14]);
+        assert_eq!(b.difference(&a).ranges(), &[5..10, 15..20]);
+        assert!(a.difference(&a).is_empty());
+    }
+
+    #[test]
+    fn test_rect() {
+        let rect = Rect::new(0..4, 0..3);
+        assert_eq!(rect.area(), 12);
+        assert!(rect.contains((3, 2)));
+        assert!(!rect.contains((4, 2)));
+        assert!(rect.contains_rect(&Rect::new(1..3, 1..2)));
+        assert_eq!(
+            rect.intersection(&Rect::new(2..6, -1..1)),
+            Some(Rect::new(2..4, 0..1))
+        );
+        assert_eq!(rect.intersection(&Rect::new(4..6, 0..3)), None);
+    }
+
+    #[test]
+    fn test_rect_subtract() {
+        let rect = Rect::new(0..4, 0..4);
+        let pieces = rect.subtract(&Rect::new(1..3, 1..3));
+        assert_eq!(pieces.len(), 4);
+        assert_eq!(pieces.iter().map(|r| r.area()).sum::<i32>(), 12);
+        assert_eq!(rect.subtract(&Rect::new(5..6, 5..6)), vec![rect.clone()]);
+        assert_eq!(rect.subtract(&Rect::new(-1..5, -1..5)), vec![]);
+    }
+
+    #[test]
+    fn test_rect_set() {
+        let a = RectSet::from_iter([Rect::new(0..4, 0..4), Rect::new(2..6, 2..6)]);
+        assert_eq!(a.area(), 28);
+        assert!(a.contains((5, 5)));
+        assert!(!a.contains((5, 0)));
+        assert!(a.contains_rect(&Rect::new(1..5, 2..4)));
+        assert!(!a.contains_rect(&Rect::new(3..6, 0..3)));
+
+        let b = RectSet::from_iter([Rect::new(3..10, 0..10)]);
+        // x == 3 for y in 0..6, and x in 4..6 for y in 2..6
+        assert_eq!(a.intersection(&b).area(), 6 + 8);
+        assert_eq!(a.difference(&b).area(), 28 - 14);
+        assert_eq!(a.union(&b).area(), 28 + 70 - 14);
+    }
+
+    #[test]
+    fn test_rect_set_merge() {
+        let set = RectSet::from_iter([
+            Rect::new(0..2, 0..2),
+            Rect::new(2..4, 0..2),
+            Rect::new(0..4, 2..3),
+        ]);
+        assert_eq!(set.rects(), &[Rect::new(0..4, 0..3)]);
+    }
+}
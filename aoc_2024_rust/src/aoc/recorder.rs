@@ -0,0 +1,274 @@
+use crate::aoc::image::{Color, Image, Palette};
+use crate::aoc::options::Options;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::hash::Hash;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Collects frames of a simulation, keeping one out of every `stride` states pushed.
+#[derive(Debug, Clone)]
+pub struct FrameRecorder {
+    stride: usize,
+    scale: usize,
+    n_pushed: usize,
+    frames: Vec<Image>,
+}
+
+impl FrameRecorder {
+    pub fn new(stride: usize, scale: usize) -> Self {
+        Self {
+            stride: stride.max(1),
+            scale: scale.max(1),
+            n_pushed: 0,
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn frames(&self) -> &[Image] {
+        &self.frames
+    }
+
+    /// Keeps the frame from `render` if it falls on the stride. `render` is only
+    /// called for kept frames, with the recorder's scale.
+    pub fn push(&mut self, render: impl FnOnce(usize) -> Image) {
+        if self.n_pushed.is_multiple_of(self.stride) {
+            self.frames.push(render(self.scale));
+        }
+        self.n_pushed += 1;
+    }
+
+    pub fn push_grid<C: Eq + Hash>(&mut self, grid: &[Vec<C>], palette: &Palette<C>) {
+        self.push(|scale| Image::from_grid(grid, palette, scale));
+    }
+
+    pub fn push_points(
+        &mut self,
+        dims: (usize, usize),
+        points: impl IntoIterator<Item = (usize, usize)>,
+        color: Color,
+        background: Color,
+    ) {
+        self.push(|scale| Image::from_points(dims, points, color, background, scale));
+    }
+
+    /// Animated GIF looping forever, with `delay` hundredths of a second per frame.
+    pub fn write_gif(&self, out: impl Write, delay: u16) -> io::Result<()> {
+        let Some(first) = self.frames.first() else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "No frames were recorded",
+            ));
+        };
+        let too_large = |_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Frames of {}x{} are too large for a GIF, at most 65535 pixels a side",
+                    first.width(),
+                    first.height()
+                ),
+            )
+        };
+        let width = u16::try_from(first.width()).map_err(too_large)?;
+        let height = u16::try_from(first.height()).map_err(too_large)?;
+        let palette = self.shared_palette();
+        let global_palette = palette
+            .as_ref()
+            .map(|(colors, _)| colors.concat())
+            .unwrap_or_default();
+
+        let mut encoder =
+            gif::Encoder::new(out, width, height, &global_palette).map_err(io::Error::other)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(io::Error::other)?;
+        for image in self.frames.iter() {
+            let mut frame = match &palette {
+                Some((_, indexes)) => gif::Frame {
+                    width,
+                    height,
+                    buffer: Cow::Owned(image.pixels().iter().map(|c| indexes[c]).collect()),
+                    ..gif::Frame::default()
+                },
+                None => gif::Frame::from_rgb_speed(width, height, &image.pixels().concat(), 10),
+            };
+            frame.delay = delay;
+            encoder.write_frame(&frame).map_err(io::Error::other)?;
+        }
+
+        Ok(())
+    }
+
+    /// Exact colours for all frames if they use 256 colours at most, otherwise
+    /// each frame gets its own quantized palette.
+    fn shared_palette(&self) -> Option<(Vec<Color>, HashMap<Color, u8>)> {
+        let mut colors = Vec::<Color>::new();
+        let mut indexes = HashMap::<Color, u8>::new();
+        for image in self.frames.iter() {
+            for color in image.pixels() {
+                if !indexes.contains_key(color) {
+                    if colors.len() == 256 {
+                        return None;
+                    }
+                    indexes.insert(*color, colors.len() as u8);
+                    colors.push(*color);
+                }
+            }
+        }
+
+        Some((colors, indexes))
+    }
+
+    pub fn save_gif(&self, path: impl AsRef<Path>, delay: u16) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write_gif(&mut out, delay)?;
+        out.flush()
+    }
+
+    /// Saves frames as `<prefix>_0000.png`, `<prefix>_0001.png`, ... in `dir`.
+    pub fn save_png_sequence(
+        &self,
+        dir: impl AsRef<Path>,
+        prefix: &str,
+    ) -> io::Result<Vec<PathBuf>> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        let mut paths = Vec::new();
+        for (ix, image) in self.frames.iter().enumerate() {
+            let path = dir.join(format!("{prefix}_{ix:04}.png"));
+            image.save(&path)?;
+            paths.push(path);
+        }
+
+        Ok(paths)
+    }
+
+    /// A `.gif` path gets an animated GIF, anything else is a directory for a PNG
+    /// sequence. Returns the paths that were written.
+    pub fn save(
+        &self,
+        path: impl AsRef<Path>,
+        prefix: &str,
+        delay: u16,
+    ) -> io::Result<Vec<PathBuf>> {
+        let path = path.as_ref();
+        if path.extension().is_some_and(|ext| ext == "gif") {
+            self.save_gif(path, delay)?;
+            Ok(vec![path.to_path_buf()])
+        } else {
+            self.save_png_sequence(path, prefix)
+        }
+    }
+}
+
+/// If `--record=<path>` is given, runs `record` on a recorder set up from
+/// `--stride` (default 1) and `--scale` (default 4), then saves the frames to
+/// `<path>` with `--delay` (default 10) hundredths of a second per GIF frame.
+pub fn record_if_requested(
+    options: &Options,
+    prefix: &str,
+    record: impl FnOnce(&mut FrameRecorder),
+) {
+    let Some(path) = options.get("record") else {
+        return;
+    };
+    let mut recorder = FrameRecorder::new(
+        options.parsed("stride").unwrap_or(1),
+        options.parsed("scale").unwrap_or(4),
+    );
+    record(&mut recorder);
+
+    let paths = recorder
+        .save(path, prefix, options.parsed("delay").unwrap_or(10))
+        .unwrap_or_else(|err| panic!("Couldn't save the recording to {path}: {err}"));
+    println!(
+        "\tRecorded {} frames to {}",
+        recorder.frames().len(),
+        if paths.len() == 1 {
+            paths[0].display().to_string()
+        } else {
+            path.to_string()
+        }
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::aoc::image::{BLACK, RED, WHITE};
+
+    fn recorded(n_frames: usize, stride: usize) -> FrameRecorder {
+        let mut recorder = FrameRecorder::new(stride, 2);
+        for ix in 0..n_frames {
+            recorder.push_points((4, 3), [(ix % 4, 1)], RED, WHITE);
+        }
+
+        recorder
+    }
+
+    #[test]
+    fn test_push_with_stride() {
+        let recorder = recorded(10, 3);
+        // Frames 0, 3, 6 and 9
+        assert_eq!(recorder.frames().len(), 4);
+        assert_eq!(recorder.frames()[1].get((6, 2)), RED);
+        assert_eq!(recorder.frames()[1].width(), 8);
+    }
+
+    #[test]
+    fn test_push_grid() {
+        let mut recorder = FrameRecorder::new(1, 1);
+        let palette = Palette::new(WHITE).with('#', BLACK);
+        recorder.push_grid(&[vec!['#', '.']], &palette);
+        assert_eq!(recorder.frames()[0].get((0, 0)), BLACK);
+        assert_eq!(recorder.frames()[0].get((1, 0)), WHITE);
+    }
+
+    #[test]
+    fn test_write_gif() {
+        let recorder = recorded(4, 1);
+        let mut out = Vec::new();
+        recorder.write_gif(&mut out, 5).unwrap();
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = options.read_info(out.as_slice()).unwrap();
+        let mut n_frames = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!((frame.width, frame.height), (8, 6));
+            assert_eq!(frame.delay, 5);
+            // Pixel (2 * n_frames, 2) is red
+            let ix = 4 * (2 * 8 + 2 * n_frames);
+            assert_eq!(&frame.buffer[ix..ix + 3], &RED);
+            n_frames += 1;
+        }
+        assert_eq!(n_frames, 4);
+    }
+
+    #[test]
+    fn test_write_gif_without_frames() {
+        let recorder = FrameRecorder::new(1, 1);
+        let err = recorder.write_gif(Vec::new(), 5).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_write_gif_too_large() {
+        let mut recorder = FrameRecorder::new(1, 1);
+        recorder.push(|_| Image::new(70_000, 1, WHITE));
+        let err = recorder.write_gif(Vec::new(), 5).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_save_png_sequence() {
+        let dir = std::env::temp_dir().join("aoc_recorder_test");
+        let paths = recorded(3, 1).save(&dir, "walk", 5).unwrap();
+        assert_eq!(paths.len(), 3);
+        assert!(paths[2].ends_with("walk_0002.png"));
+        assert!(paths.iter().all(|path| path.exists()));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
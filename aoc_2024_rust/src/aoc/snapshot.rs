@@ -0,0 +1,148 @@
+use crate::aoc::image::Image;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn snapshot_path(name: &str, extension: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("snapshots")
+        .join(format!("{name}.{extension}"))
+}
+
+fn updating() -> bool {
+    env::var("UPDATE_SNAPSHOTS").is_ok_and(|value| !value.is_empty() && value != "0")
+}
+
+fn write_snapshot(path: &Path, contents: &[u8]) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents)
+        .unwrap_or_else(|err| panic!("Couldn't write snapshot {}: {err}", path.display()));
+}
+
+/// Lines of `expected` and `actual`, kept lines prefixed with two spaces,
+/// removed ones with `-` and added ones with `+`.
+fn diff_lines(expected: &str, actual: &str) -> String {
+    let old = expected.lines().collect::<Vec<&str>>();
+    let new = actual.lines().collect::<Vec<&str>>();
+    // Longest common subsequence of the lines after each position
+    let mut lcs = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::<String>::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            diff.push(format!("  {}", old[i]));
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            diff.push(format!("- {}", old[i]));
+            i += 1;
+        } else {
+            diff.push(format!("+ {}", new[j]));
+            j += 1;
+        }
+    }
+
+    diff.join("\n")
+}
+
+/// The diff of `expected` and `actual`, which `diff_lines` leaves without any
+/// changed line when only the newlines at the end differ, so those are told apart.
+fn describe_difference(expected: &str, actual: &str) -> String {
+    let trailing = |text: &str| text.len() - text.trim_end_matches('\n').len();
+    let mut description = diff_lines(expected, actual);
+    if trailing(expected) != trailing(actual) {
+        description.push_str(&format!(
+            "\n(expected {} trailing newlines, found {})",
+            trailing(expected),
+            trailing(actual)
+        ));
+    }
+
+    description
+}
+
+/// Checks `actual` against the `snapshots/<name>.txt` snapshot and fails with
+/// a diff when they differ. `\r\n` line endings count as `\n`, so a checkout
+/// that converts them still matches. With `UPDATE_SNAPSHOTS=1` the snapshot is written
+/// instead, to create or accept it.
+pub fn assert_text(name: &str, actual: &str) {
+    let path = snapshot_path(name, "txt");
+    if updating() {
+        write_snapshot(&path, actual.as_bytes());
+        return;
+    }
+    let Ok(expected) = fs::read_to_string(&path) else {
+        panic!(
+            "Missing snapshot {}, run with UPDATE_SNAPSHOTS=1 to create it",
+            path.display()
+        );
+    };
+    let (expected, actual) = (expected.replace("\r\n", "\n"), actual.replace("\r\n", "\n"));
+    if expected != actual {
+        panic!(
+            "Snapshot {name} doesn't match (- expected, + actual):\n{}",
+            describe_difference(&expected, &actual)
+        );
+    }
+}
+
+/// Checks `image` against the `snapshots/<name>.ppm` snapshot like `assert_text`.
+/// On a mismatch the image is saved next to it as `<name>.new.ppm` to compare them.
+pub fn assert_image(name: &str, image: &Image) {
+    let path = snapshot_path(name, "ppm");
+    let mut actual = Vec::<u8>::new();
+    image.write_ppm(&mut actual).unwrap();
+    if updating() {
+        write_snapshot(&path, &actual);
+        return;
+    }
+    let Ok(expected) = fs::read(&path) else {
+        panic!(
+            "Missing snapshot {}, run with UPDATE_SNAPSHOTS=1 to create it",
+            path.display()
+        );
+    };
+    if expected != actual {
+        let new_path = snapshot_path(name, "new.ppm");
+        write_snapshot(&new_path, &actual);
+        panic!(
+            "Snapshot {name} doesn't match, the new image is in {}",
+            new_path.display()
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines() {
+        assert_eq!(diff_lines("a\nb\nc", "a\nb\nc"), "  a\n  b\n  c");
+        assert_eq!(
+            diff_lines("a\nb\nc", "a\nx\nc\nd"),
+            "  a\n- b\n+ x\n  c\n+ d"
+        );
+        assert_eq!(diff_lines("", "a"), "+ a");
+        assert_eq!(diff_lines("a\nb", "b"), "- a\n  b");
+    }
+
+    #[test]
+    fn test_describe_difference() {
+        assert_eq!(
+            describe_difference("a\nb\n", "a\nb"),
+            "  a\n  b\n(expected 1 trailing newlines, found 0)"
+        );
+        assert_eq!(describe_difference("a\nb", "a\nc"), "  a\n- b\n+ c");
+    }
+}
@@ -0,0 +1,338 @@
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+/// Where the viewer draws its frames
+pub trait Backend {
+    fn draw(&mut self, frame: &str, status: &str) -> io::Result<()>;
+}
+
+/// Redraws in place on an ANSI terminal
+pub struct AnsiBackend<W: Write> {
+    out: W,
+}
+
+impl<W: Write> AnsiBackend<W> {
+    pub fn new(out: W) -> Self {
+        Self { out }
+    }
+}
+
+impl<W: Write> Backend for AnsiBackend<W> {
+    fn draw(&mut self, frame: &str, status: &str) -> io::Result<()> {
+        // Move the cursor home and clear the screen
+        write!(self.out, "\x1b[H\x1b[2J{frame}\n\n{status}\n> ")?;
+        self.out.flush()
+    }
+}
+
+/// Keeps every drawn frame, for tests
+#[derive(Debug, Default)]
+pub struct HeadlessBackend {
+    pub frames: Vec<String>,
+    pub statuses: Vec<String>,
+}
+
+impl Backend for HeadlessBackend {
+    fn draw(&mut self, frame: &str, status: &str) -> io::Result<()> {
+        self.frames.push(frame.to_string());
+        self.statuses.push(status.to_string());
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Command {
+    Next,
+    Prev,
+    Jump(usize),
+    Play,
+    Pause,
+    Quit,
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    /// `n` (or an empty line), `p`, `g <step>` (or just `<step>`), `play`, `pause`, `q`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s {
+            "" | "n" | "next" => Ok(Command::Next),
+            "p" | "prev" => Ok(Command::Prev),
+            "play" => Ok(Command::Play),
+            "pause" | "s" | "stop" => Ok(Command::Pause),
+            "q" | "quit" => Ok(Command::Quit),
+            _ => s
+                .trim_start_matches('g')
+                .trim()
+                .parse::<usize>()
+                .map(Command::Jump)
+                .map_err(|_| format!("Unknown command: {s}")),
+        }
+    }
+}
+
+const HELP: &str = "[n]ext, [p]rev, [g] <step>, play, pause, [q]uit";
+
+/// Steps through a simulation given by its `initial` state and a `step` function,
+/// which returns `None` once the simulation is over. Visited states are kept so
+/// the viewer can step back.
+pub struct Viewer<S, F, R> {
+    history: Vec<S>,
+    position: usize,
+    finished: bool,
+    playing: bool,
+    step: F,
+    render: R,
+}
+
+impl<S, F, R> Viewer<S, F, R>
+where
+    F: Fn(&S) -> Option<S>,
+    R: Fn(&S) -> String,
+{
+    pub fn new(initial: S, step: F, render: R) -> Self {
+        Self {
+            history: vec![initial],
+            position: 0,
+            finished: false,
+            playing: false,
+            step,
+            render,
+        }
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn current(&self) -> &S {
+        &self.history[self.position]
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Returns false if the simulation is already over.
+    pub fn forward(&mut self) -> bool {
+        if self.position + 1 == self.history.len() {
+            if self.finished {
+                return false;
+            }
+            match (self.step)(self.current()) {
+                Some(state) => self.history.push(state),
+                None => {
+                    self.finished = true;
+                    return false;
+                }
+            }
+        }
+        self.position += 1;
+        true
+    }
+
+    pub fn back(&mut self) -> bool {
+        if self.position == 0 {
+            return false;
+        }
+        self.position -= 1;
+        true
+    }
+
+    /// Goes to step `n`, or the last step if the simulation ends before it.
+    pub fn jump(&mut self, n: usize) {
+        if n < self.history.len() {
+            self.position = n;
+        } else {
+            self.position = self.history.len() - 1;
+            while self.position < n && self.forward() {}
+        }
+    }
+
+    /// Returns false on `Quit`.
+    pub fn apply(&mut self, command: Command) -> bool {
+        match command {
+            Command::Next => {
+                self.forward();
+            }
+            Command::Prev => {
+                self.back();
+            }
+            Command::Jump(n) => self.jump(n),
+            Command::Play => self.playing = true,
+            Command::Pause => self.playing = false,
+            Command::Quit => return false,
+        }
+
+        true
+    }
+
+    pub fn draw(&self, backend: &mut impl Backend) -> io::Result<()> {
+        let state = if self.playing { "playing" } else { "paused" };
+        let end = if self.finished && self.position + 1 == self.history.len() {
+            " (end)"
+        } else {
+            ""
+        };
+        let status = format!("step {}{end} | {state} | {HELP}", self.position);
+        backend.draw(&(self.render)(self.current()), &status)
+    }
+
+    /// Draws after every command from `commands`, advancing one step every
+    /// `delay` while playing. Stops on `Quit` or once `commands` disconnects.
+    pub fn run(
+        &mut self,
+        backend: &mut impl Backend,
+        commands: &Receiver<Command>,
+        delay: Duration,
+    ) -> io::Result<()> {
+        self.draw(backend)?;
+        loop {
+            let command = if self.playing {
+                match commands.recv_timeout(delay) {
+                    Ok(command) => Some(command),
+                    Err(RecvTimeoutError::Timeout) => None,
+                    // Keep playing to the end without input
+                    Err(RecvTimeoutError::Disconnected) => {
+                        thread::sleep(delay);
+                        None
+                    }
+                }
+            } else {
+                match commands.recv() {
+                    Ok(command) => Some(command),
+                    Err(_) => return Ok(()),
+                }
+            };
+
+            match command {
+                Some(command) => {
+                    if !self.apply(command) {
+                        return Ok(());
+                    }
+                }
+                None => {
+                    if !self.forward() {
+                        self.playing = false;
+                    }
+                }
+            }
+            self.draw(backend)?;
+        }
+    }
+}
+
+/// Runs `viewer` on the terminal, reading one command per line from stdin.
+pub fn run_in_terminal<S, F, R>(viewer: &mut Viewer<S, F, R>, delay: Duration) -> io::Result<()>
+where
+    F: Fn(&S) -> Option<S>,
+    R: Fn(&S) -> String,
+{
+    let (sender, commands) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            match line.parse::<Command>() {
+                Ok(command) => {
+                    if sender.send(command).is_err() {
+                        break;
+                    }
+                }
+                Err(err) => eprintln!("{err}"),
+            }
+        }
+    });
+
+    viewer.run(&mut AnsiBackend::new(io::stdout()), &commands, delay)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn counter() -> Viewer<u32, impl Fn(&u32) -> Option<u32>, impl Fn(&u32) -> String> {
+        Viewer::new(
+            0,
+            |n: &u32| if *n < 5 { Some(n + 1) } else { None },
+            |n: &u32| format!("n = {n}"),
+        )
+    }
+
+    fn run_headless(commands: &[Command]) -> HeadlessBackend {
+        let (sender, receiver) = mpsc::channel();
+        for command in commands {
+            sender.send(*command).unwrap();
+        }
+        drop(sender);
+
+        let mut backend = HeadlessBackend::default();
+        counter()
+            .run(&mut backend, &receiver, Duration::from_millis(1))
+            .unwrap();
+        backend
+    }
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!("".parse(), Ok(Command::Next));
+        assert_eq!("p".parse(), Ok(Command::Prev));
+        assert_eq!("g 12".parse(), Ok(Command::Jump(12)));
+        assert_eq!("7".parse(), Ok(Command::Jump(7)));
+        assert_eq!("play".parse(), Ok(Command::Play));
+        assert_eq!("q".parse(), Ok(Command::Quit));
+        assert!("jump".parse::<Command>().is_err());
+    }
+
+    #[test]
+    fn test_forward_and_back() {
+        let mut viewer = counter();
+        assert!(!viewer.back());
+        for _ in 0..5 {
+            assert!(viewer.forward());
+        }
+        assert!(!viewer.forward());
+        assert_eq!(*viewer.current(), 5);
+        assert!(viewer.back());
+        assert_eq!(*viewer.current(), 4);
+    }
+
+    #[test]
+    fn test_jump() {
+        let mut viewer = counter();
+        viewer.jump(3);
+        assert_eq!((viewer.position(), *viewer.current()), (3, 3));
+        viewer.jump(1);
+        assert_eq!(*viewer.current(), 1);
+        viewer.jump(100);
+        assert_eq!(*viewer.current(), 5);
+    }
+
+    #[test]
+    fn test_run_headless() {
+        use Command::*;
+        let backend = run_headless(&[Next, Next, Prev, Jump(4), Quit, Next]);
+        assert_eq!(
+            backend.frames,
+            vec!["n = 0", "n = 1", "n = 2", "n = 1", "n = 4"]
+        );
+        assert!(backend.statuses[4].starts_with("step 4 | paused"));
+    }
+
+    #[test]
+    fn test_run_headless_play() {
+        // Plays to the end, then waits for commands while paused
+        let backend = run_headless(&[Command::Play]);
+        assert_eq!(backend.frames.last().unwrap(), "n = 5");
+        assert!(backend
+            .statuses
+            .last()
+            .unwrap()
+            .starts_with("step 5 (end) | paused"));
+    }
+}
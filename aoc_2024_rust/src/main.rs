@@ -1,12 +1,19 @@
-mod aoc;
+use aoc_2024_rust::aoc;
 
 fn main() {
     let args = std::env::args().collect::<Vec<String>>();
 
     if args.len() < 2 {
         aoc::run_all()
+    } else if args[1] == "gen" {
+        aoc::gen::run(&args[2..]);
     } else {
         let day = args[1].parse::<usize>().unwrap();
-        aoc::run(day);
+        let options = aoc::options::Options::parse(&args[2..]);
+        if options.is_empty() {
+            aoc::run(day);
+        } else {
+            aoc::run_with_options(day, &options);
+        }
     }
 }